@@ -1,6 +1,6 @@
 use node_template_runtime::{
-	AccountId, AuraConfig, BalancesConfig, GrandpaConfig, RuntimeGenesisConfig, Signature,
-	SudoConfig, SystemConfig, WASM_BINARY,
+	AccountId, AuraConfig, BalancesConfig, DayAheadMarketConfig, GrandpaConfig,
+	IntradayMarketConfig, RuntimeGenesisConfig, Signature, SudoConfig, SystemConfig, WASM_BINARY,
 };
 use sc_service::ChainType;
 use sp_consensus_aura::sr25519::AuthorityId as AuraId;
@@ -154,6 +154,7 @@ fn testnet_genesis(
 			key: Some(root_key),
 		},
 		transaction_payment: Default::default(),
-		energy_bidding_module: node_template_runtime::energy_bidding::GenesisConfig { auction_index: 0 },
+		day_ahead_market: DayAheadMarketConfig { auction_index: 0 },
+		intraday_market: IntradayMarketConfig { auction_index: 0 },
 	}
 }
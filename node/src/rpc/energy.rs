@@ -0,0 +1,854 @@
+//! The `energy_*` RPC namespace, answering market queries through the `EnergyBiddingApi`
+//! runtime API so that clients do not need to decode raw storage, and pushing auction events
+//! to subscribers as blocks are imported. Both serve the day-ahead market unless the
+//! intraday market is selected.
+
+use std::{marker::PhantomData, sync::Arc};
+
+use energy_bidding_runtime_api::{
+	AuctionDataFor, EnergyBiddingApi as EnergyBiddingRuntimeApi, Market,
+};
+use futures::{future, stream, FutureExt, StreamExt};
+use jsonrpsee::{
+	core::{Error as JsonRpseeError, RpcResult},
+	proc_macros::rpc,
+	types::{
+		error::{CallError, ErrorObject},
+		SubscriptionResult,
+	},
+	SubscriptionSink,
+};
+use node_template_runtime::{
+	energy_bidding::{self, BidQuote, PartyType},
+	opaque::Block,
+	AccountId, Balance, BlockNumber, Hash, Runtime, RuntimeEvent, StorageValue as _,
+};
+use sc_client_api::{Backend, BlockchainEvents, StorageProvider};
+use sc_rpc::SubscriptionTaskExecutor;
+use serde::{Deserialize, Serialize};
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_core::{storage::StorageKey, traits::SpawnNamed};
+use sp_runtime::{codec::Decode, traits::Block as BlockT};
+
+/// Auction id type of the runtime.
+pub type AuctionId = u64;
+/// Price type of the runtime.
+pub type Price = u128;
+/// Energy quantity type of the runtime.
+pub type Quantity = u128;
+/// An auction as returned by the RPC.
+pub type Auction = AuctionDataFor<AccountId, AuctionId, Price, Quantity, BlockNumber>;
+
+/// Market queries. Every method answers at the best block unless `at` is given, and from the
+/// day-ahead market unless `market` is given. Private auctions are hidden from
+/// `energy_listOpenAuctions` only; requests are not authenticated, so the other methods return
+/// them by id.
+#[rpc(server)]
+pub trait EnergyApi<BlockHash> {
+	/// The auction with the given id, if it is still listed.
+	#[method(name = "energy_getAuction")]
+	fn get_auction(
+		&self,
+		auction_id: AuctionId,
+		at: Option<BlockHash>,
+		market: Option<Market>,
+	) -> RpcResult<Option<Auction>>;
+
+	/// A page of the public auctions still accepting bids, ordered by id and optionally
+	/// restricted to one tier.
+	#[method(name = "energy_listOpenAuctions")]
+	fn list_open_auctions(
+		&self,
+		tier: Option<u32>,
+		page: u32,
+		page_size: u32,
+		at: Option<BlockHash>,
+		market: Option<Market>,
+	) -> RpcResult<Vec<Auction>>;
+
+	/// A page of the auctions an account took part in, oldest first.
+	#[method(name = "energy_accountHistory")]
+	fn account_history(
+		&self,
+		account: AccountId,
+		page: u32,
+		page_size: u32,
+		at: Option<BlockHash>,
+		market: Option<Market>,
+	) -> RpcResult<Vec<(AuctionId, PartyType)>>;
+
+	/// What a bid would reserve from the bidder and the fee taken if it wins.
+	#[method(name = "energy_quoteBid")]
+	fn quote_bid(
+		&self,
+		auction_id: AuctionId,
+		bid: Price,
+		at: Option<BlockHash>,
+		market: Option<Market>,
+	) -> RpcResult<Option<BidQuote<Price, Balance>>>;
+}
+
+/// Error type of this RPC api.
+pub enum Error {
+	/// The call to the runtime failed.
+	RuntimeError,
+}
+
+impl From<Error> for i32 {
+	fn from(e: Error) -> i32 {
+		match e {
+			Error::RuntimeError => 1,
+		}
+	}
+}
+
+fn runtime_error(message: &str, e: sp_api::ApiError) -> JsonRpseeError {
+	CallError::Custom(ErrorObject::owned(Error::RuntimeError.into(), message, Some(e.to_string())))
+		.into()
+}
+
+/// Provides the `energy_*` RPC methods.
+pub struct Energy<C, Block> {
+	client: Arc<C>,
+	_marker: PhantomData<Block>,
+}
+
+impl<C, Block> Energy<C, Block> {
+	/// Creates a new instance of the energy market RPC handler.
+	pub fn new(client: Arc<C>) -> Self {
+		Self { client, _marker: Default::default() }
+	}
+}
+
+impl<C, Block> Energy<C, Block>
+where
+	Block: BlockT,
+	C: HeaderBackend<Block>,
+{
+	fn at_or_best(&self, at: Option<<Block as BlockT>::Hash>) -> <Block as BlockT>::Hash {
+		at.unwrap_or_else(|| self.client.info().best_hash)
+	}
+}
+
+impl<C, Block> EnergyApiServer<<Block as BlockT>::Hash> for Energy<C, Block>
+where
+	Block: BlockT,
+	C: ProvideRuntimeApi<Block> + HeaderBackend<Block> + Send + Sync + 'static,
+	C::Api:
+		EnergyBiddingRuntimeApi<Block, AccountId, AuctionId, Price, Quantity, BlockNumber, Balance>,
+{
+	fn get_auction(
+		&self,
+		auction_id: AuctionId,
+		at: Option<<Block as BlockT>::Hash>,
+		market: Option<Market>,
+	) -> RpcResult<Option<Auction>> {
+		self.client
+			.runtime_api()
+			.auction(self.at_or_best(at), market.unwrap_or_default(), auction_id)
+			.map_err(|e| runtime_error("Unable to query auction.", e))
+	}
+
+	fn list_open_auctions(
+		&self,
+		tier: Option<u32>,
+		page: u32,
+		page_size: u32,
+		at: Option<<Block as BlockT>::Hash>,
+		market: Option<Market>,
+	) -> RpcResult<Vec<Auction>> {
+		self.client
+			.runtime_api()
+			.open_auctions(self.at_or_best(at), market.unwrap_or_default(), tier, page, page_size)
+			.map_err(|e| runtime_error("Unable to query open auctions.", e))
+	}
+
+	fn account_history(
+		&self,
+		account: AccountId,
+		page: u32,
+		page_size: u32,
+		at: Option<<Block as BlockT>::Hash>,
+		market: Option<Market>,
+	) -> RpcResult<Vec<(AuctionId, PartyType)>> {
+		self.client
+			.runtime_api()
+			.account_history(
+				self.at_or_best(at),
+				market.unwrap_or_default(),
+				account,
+				page,
+				page_size,
+			)
+			.map_err(|e| runtime_error("Unable to query account history.", e))
+	}
+
+	fn quote_bid(
+		&self,
+		auction_id: AuctionId,
+		bid: Price,
+		at: Option<<Block as BlockT>::Hash>,
+		market: Option<Market>,
+	) -> RpcResult<Option<BidQuote<Price, Balance>>> {
+		self.client
+			.runtime_api()
+			.quote_bid(self.at_or_best(at), market.unwrap_or_default(), auction_id, bid)
+			.map_err(|e| runtime_error("Unable to quote bid.", e))
+	}
+}
+
+/// Which blocks a subscription follows.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum BlockMode {
+	/// New best blocks, as soon as they are imported. Events may be reverted by a reorg.
+	#[default]
+	Best,
+	/// Finalized blocks only.
+	Finalized,
+}
+
+/// An auction lifecycle event pushed to subscribers.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", tag = "event")]
+pub enum AuctionEvent {
+	/// A new auction was listed.
+	#[serde(rename_all = "camelCase")]
+	AuctionCreated {
+		/// Id of the auction.
+		auction_id: AuctionId,
+		/// Seller of the energy.
+		seller_id: AccountId,
+		/// Energy quantity on offer.
+		energy_quantity: Quantity,
+		/// The seller's starting price.
+		starting_price: Price,
+	},
+	/// A bid was placed on an auction.
+	#[serde(rename_all = "camelCase")]
+	AuctionBidAdded {
+		/// Id of the auction.
+		auction_id: AuctionId,
+		/// Seller of the energy.
+		seller_id: AccountId,
+		/// Account that placed the bid.
+		bidder: AccountId,
+		/// The bid price.
+		bid: Price,
+	},
+	/// An auction was settled with its highest bidder.
+	#[serde(rename_all = "camelCase")]
+	AuctionExecuted {
+		/// Id of the auction.
+		auction_id: AuctionId,
+		/// Seller of the energy.
+		seller_id: AccountId,
+		/// Winning bidder.
+		buyer_id: AccountId,
+		/// Energy quantity traded.
+		energy_quantity: Quantity,
+		/// The clearing price.
+		highest_bid: Price,
+		/// Block at which the auction was executed.
+		executed_at: BlockNumber,
+	},
+}
+
+impl AuctionEvent {
+	fn from_runtime_event(event: RuntimeEvent) -> Option<(Market, Self)> {
+		match event {
+			RuntimeEvent::DayAheadMarket(event) =>
+				Some((Market::DayAhead, Self::from_market_event(event)?)),
+			RuntimeEvent::IntradayMarket(event) =>
+				Some((Market::Intraday, Self::from_market_event(event)?)),
+			_ => None,
+		}
+	}
+
+	fn from_market_event<I: 'static>(event: energy_bidding::Event<Runtime, I>) -> Option<Self>
+	where
+		Runtime:
+			energy_bidding::Config<I, AuctionId = AuctionId, Price = Price, Quantity = Quantity>,
+	{
+		match event {
+			energy_bidding::Event::AuctionCreated {
+				auction_id,
+				seller_id,
+				energy_quantity,
+				starting_price,
+				..
+			} => Some(Self::AuctionCreated {
+				auction_id,
+				seller_id,
+				energy_quantity,
+				starting_price,
+			}),
+			energy_bidding::Event::AuctionBidAdded { auction_id, seller_id, bid, .. } =>
+				Some(Self::AuctionBidAdded {
+					auction_id,
+					seller_id,
+					bidder: bid.bidder,
+					bid: bid.bid,
+				}),
+			energy_bidding::Event::AuctionExecuted {
+				auction_id,
+				seller_id,
+				buyer_id,
+				energy_quantity,
+				highest_bid,
+				executed_at,
+				..
+			} => Some(Self::AuctionExecuted {
+				auction_id,
+				seller_id,
+				buyer_id,
+				energy_quantity,
+				highest_bid,
+				executed_at,
+			}),
+			_ => None,
+		}
+	}
+
+	/// The auction the event belongs to.
+	pub fn auction_id(&self) -> AuctionId {
+		match self {
+			Self::AuctionCreated { auction_id, .. } |
+			Self::AuctionBidAdded { auction_id, .. } |
+			Self::AuctionExecuted { auction_id, .. } => *auction_id,
+		}
+	}
+
+	/// Whether the account is the seller, bidder or buyer of the event.
+	pub fn involves(&self, who: &AccountId) -> bool {
+		match self {
+			Self::AuctionCreated { seller_id, .. } => seller_id == who,
+			Self::AuctionBidAdded { seller_id, bidder, .. } => seller_id == who || bidder == who,
+			Self::AuctionExecuted { seller_id, buyer_id, .. } =>
+				seller_id == who || buyer_id == who,
+		}
+	}
+}
+
+/// An auction event together with the block and market it was emitted in.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuctionNotification {
+	/// Hash of the block that emitted the event.
+	pub block_hash: Hash,
+	/// The market the auction is listed in.
+	pub market: Market,
+	/// The event.
+	pub event: AuctionEvent,
+}
+
+/// Decodes the auction events of both markets out of the encoded `System::Events` of a block.
+/// Events that were encoded by an incompatible runtime are skipped.
+fn decode_auction_events(encoded: &[u8]) -> Vec<(Market, AuctionEvent)> {
+	let records: Vec<frame_system::EventRecord<RuntimeEvent, Hash>> =
+		Decode::decode(&mut &encoded[..]).unwrap_or_default();
+
+	records
+		.into_iter()
+		.filter_map(|record| AuctionEvent::from_runtime_event(record.event))
+		.collect()
+}
+
+/// Auction event subscriptions.
+#[rpc(server)]
+pub trait EnergySubscriptionApi {
+	/// Pushes the events of one auction. `mode` defaults to following best blocks and `market`
+	/// to the day-ahead market.
+	#[subscription(
+		name = "energy_subscribeAuction" => "energy_auctionEvent",
+		unsubscribe = "energy_unsubscribeAuction",
+		item = AuctionNotification,
+	)]
+	fn subscribe_auction(
+		&self,
+		auction_id: AuctionId,
+		mode: Option<BlockMode>,
+		market: Option<Market>,
+	);
+
+	/// Pushes the events of every auction an account sells in, bids in or wins, in either
+	/// market unless `market` is given. `mode` defaults to following best blocks.
+	#[subscription(
+		name = "energy_subscribeAccount" => "energy_accountEvent",
+		unsubscribe = "energy_unsubscribeAccount",
+		item = AuctionNotification,
+	)]
+	fn subscribe_account(
+		&self,
+		account: AccountId,
+		mode: Option<BlockMode>,
+		market: Option<Market>,
+	);
+}
+
+/// Provides the `energy_*` subscriptions.
+pub struct EnergySubscriptions<C, B> {
+	client: Arc<C>,
+	executor: SubscriptionTaskExecutor,
+	_marker: PhantomData<B>,
+}
+
+impl<C, B> EnergySubscriptions<C, B> {
+	/// Creates a new instance of the energy market subscription handler.
+	pub fn new(client: Arc<C>, executor: SubscriptionTaskExecutor) -> Self {
+		Self { client, executor, _marker: Default::default() }
+	}
+}
+
+impl<C, B> EnergySubscriptions<C, B>
+where
+	B: Backend<Block> + Send + Sync + 'static,
+	C: BlockchainEvents<Block> + StorageProvider<Block, B> + Send + Sync + 'static,
+{
+	fn subscribe(
+		&self,
+		mut sink: SubscriptionSink,
+		mode: BlockMode,
+		filter: impl Fn(Market, &AuctionEvent) -> bool + Send + 'static,
+	) -> SubscriptionResult {
+		let blocks = match mode {
+			BlockMode::Best => self
+				.client
+				.import_notification_stream()
+				.filter(|notification| future::ready(notification.is_new_best))
+				.map(|notification| notification.hash)
+				.boxed(),
+			// Blocks finalized implicitly by a later one are reported in its tree route.
+			BlockMode::Finalized => self
+				.client
+				.finality_notification_stream()
+				.flat_map(|notification| {
+					let mut hashes = notification.tree_route.to_vec();
+					hashes.push(notification.hash);
+					stream::iter(hashes)
+				})
+				.boxed(),
+		};
+
+		let events_key = StorageKey(frame_system::Events::<Runtime>::hashed_key().to_vec());
+		let client = self.client.clone();
+		let notifications = blocks
+			.flat_map(move |block_hash| {
+				let events = match client.storage(block_hash, &events_key) {
+					Ok(Some(encoded)) => decode_auction_events(&encoded.0),
+					_ => Vec::new(),
+				};
+				stream::iter(
+					events
+						.into_iter()
+						.filter(|(market, event)| filter(*market, event))
+						.map(move |(market, event)| AuctionNotification {
+							block_hash,
+							market,
+							event,
+						})
+						.collect::<Vec<_>>(),
+				)
+			})
+			.boxed();
+
+		let fut = async move {
+			sink.pipe_from_stream(notifications).await;
+		};
+		self.executor.spawn("energy-rpc-subscription", Some("rpc"), fut.boxed());
+
+		Ok(())
+	}
+}
+
+impl<C, B> EnergySubscriptionApiServer for EnergySubscriptions<C, B>
+where
+	B: Backend<Block> + Send + Sync + 'static,
+	C: BlockchainEvents<Block> + StorageProvider<Block, B> + Send + Sync + 'static,
+{
+	fn subscribe_auction(
+		&self,
+		sink: SubscriptionSink,
+		auction_id: AuctionId,
+		mode: Option<BlockMode>,
+		market: Option<Market>,
+	) -> SubscriptionResult {
+		let market = market.unwrap_or_default();
+		self.subscribe(sink, mode.unwrap_or_default(), move |event_market, event| {
+			event_market == market && event.auction_id() == auction_id
+		})
+	}
+
+	fn subscribe_account(
+		&self,
+		sink: SubscriptionSink,
+		account: AccountId,
+		mode: Option<BlockMode>,
+		market: Option<Market>,
+	) -> SubscriptionResult {
+		self.subscribe(sink, mode.unwrap_or_default(), move |event_market, event| {
+			market.map_or(true, |market| event_market == market) && event.involves(&account)
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use futures::executor::block_on;
+	use node_template_runtime::energy_bidding::{AuctionStatus, Bid, Tier};
+	use sp_api::{ApiError, ApiRef};
+	use sp_blockchain::{BlockStatus, Info};
+	use sp_core::H256;
+	use sp_keyring::AccountKeyring;
+	use sp_runtime::{codec::Encode, traits::NumberFor};
+
+	fn best_hash() -> H256 {
+		H256::repeat_byte(1)
+	}
+
+	fn test_auction(auction_id: AuctionId, level: u32) -> Auction {
+		let starting_bid = Bid { bidder: AccountKeyring::Alice.to_account_id(), bid: 1_000 };
+		Auction {
+			auction_id,
+			seller_id: AccountKeyring::Alice.to_account_id(),
+			quantity: 2,
+			starting_bid: starting_bid.clone(),
+			bids: vec![starting_bid.clone()],
+			auction_period: 50,
+			auction_status: AuctionStatus::Open,
+			start_at: 1,
+			end_at: 51,
+			highest_bid: starting_bid,
+			auction_category: Tier { level },
+			min_bidder_reputation: None,
+			delivery_period: 0,
+		}
+	}
+
+	/// A client whose runtime knows day-ahead auctions 0 (tier 1) and 1 (tier 2), but only at
+	/// the best block.
+	#[derive(Clone)]
+	struct TestClient;
+
+	#[derive(Clone)]
+	struct TestApi;
+
+	impl ProvideRuntimeApi<Block> for TestClient {
+		type Api = TestApi;
+
+		fn runtime_api(&self) -> ApiRef<Self::Api> {
+			TestApi.into()
+		}
+	}
+
+	impl HeaderBackend<Block> for TestClient {
+		fn header(&self, _: H256) -> sp_blockchain::Result<Option<<Block as BlockT>::Header>> {
+			Ok(None)
+		}
+
+		fn info(&self) -> Info<Block> {
+			Info {
+				best_hash: best_hash(),
+				best_number: 1,
+				genesis_hash: Default::default(),
+				finalized_hash: Default::default(),
+				finalized_number: 0,
+				finalized_state: None,
+				number_leaves: 1,
+				block_gap: None,
+			}
+		}
+
+		fn status(&self, _: H256) -> sp_blockchain::Result<BlockStatus> {
+			Ok(BlockStatus::Unknown)
+		}
+
+		fn number(&self, _: H256) -> sp_blockchain::Result<Option<NumberFor<Block>>> {
+			Ok(None)
+		}
+
+		fn hash(&self, _: NumberFor<Block>) -> sp_blockchain::Result<Option<H256>> {
+			Ok(None)
+		}
+	}
+
+	sp_api::mock_impl_runtime_apis! {
+		impl EnergyBiddingRuntimeApi<
+			Block,
+			AccountId,
+			AuctionId,
+			Price,
+			Quantity,
+			BlockNumber,
+			Balance,
+		> for TestApi {
+			#[advanced]
+			fn open_auctions(
+				at: <Block as BlockT>::Hash,
+				market: Market,
+				tier: Option<u32>,
+				page: u32,
+				page_size: u32,
+			) -> Result<Vec<Auction>, ApiError> {
+				if at != best_hash() || market != Market::DayAhead {
+					return Ok(vec![])
+				}
+				Ok(vec![test_auction(0, 1), test_auction(1, 2)]
+					.into_iter()
+					.filter(|auction| {
+						tier.map_or(true, |level| auction.auction_category.level == level)
+					})
+					.skip((page * page_size) as usize)
+					.take(page_size as usize)
+					.collect())
+			}
+
+			#[advanced]
+			fn auction(
+				at: <Block as BlockT>::Hash,
+				market: Market,
+				auction_id: AuctionId,
+			) -> Result<Option<Auction>, ApiError> {
+				Ok((at == best_hash() && market == Market::DayAhead && auction_id < 2)
+					.then(|| test_auction(auction_id, auction_id as u32 + 1)))
+			}
+
+			fn bids_of(
+				_market: Market,
+				_account: AccountId,
+			) -> Vec<(AuctionId, Bid<AccountId, Price>)> {
+				vec![]
+			}
+
+			fn highest_bid(
+				_market: Market,
+				_auction_id: AuctionId,
+			) -> Option<Bid<AccountId, Price>> {
+				None
+			}
+
+			fn time_remaining(_market: Market, _auction_id: AuctionId) -> Option<BlockNumber> {
+				None
+			}
+
+			fn account_history(
+				market: Market,
+				account: AccountId,
+				page: u32,
+				_page_size: u32,
+			) -> Vec<(AuctionId, PartyType)> {
+				if market == Market::DayAhead &&
+					account == AccountKeyring::Alice.to_account_id() &&
+					page == 0
+				{
+					vec![(0, PartyType::Seller), (1, PartyType::Seller)]
+				} else {
+					vec![]
+				}
+			}
+
+			#[advanced]
+			fn quote_bid(
+				_at: <Block as BlockT>::Hash,
+				_market: Market,
+				auction_id: AuctionId,
+				bid: Price,
+			) -> Result<Option<BidQuote<Price, Balance>>, ApiError> {
+				if auction_id > 1 {
+					return Err(ApiError::Application("auction state is unavailable".into()))
+				}
+				Ok(Some(BidQuote {
+					highest_bid: 1_000,
+					accepted: bid > 1_000,
+					reserved: bid,
+					fee: bid / 100,
+					seller_receives: bid - bid / 100,
+				}))
+			}
+		}
+	}
+
+	fn rpc() -> jsonrpsee::RpcModule<Energy<TestClient, Block>> {
+		Energy::new(Arc::new(TestClient)).into_rpc()
+	}
+
+	#[test]
+	fn get_auction_should_default_to_best_block() {
+		let rpc = rpc();
+
+		let auction: Option<Auction> =
+			block_on(rpc.call("energy_getAuction", (0u64, None::<H256>))).unwrap();
+		assert_eq!(auction, Some(test_auction(0, 1)));
+
+		let auction: Option<Auction> =
+			block_on(rpc.call("energy_getAuction", (0u64, Some(H256::repeat_byte(2))))).unwrap();
+		assert_eq!(auction, None);
+
+		let auction: Option<Auction> =
+			block_on(rpc.call("energy_getAuction", (5u64, Some(best_hash())))).unwrap();
+		assert_eq!(auction, None);
+	}
+
+	#[test]
+	fn queries_should_select_the_market() {
+		let rpc = rpc();
+
+		let auction: Option<Auction> =
+			block_on(rpc.call("energy_getAuction", (0u64, None::<H256>, Some(Market::DayAhead))))
+				.unwrap();
+		assert_eq!(auction, Some(test_auction(0, 1)));
+
+		let auction: Option<Auction> =
+			block_on(rpc.call("energy_getAuction", (0u64, None::<H256>, Some(Market::Intraday))))
+				.unwrap();
+		assert_eq!(auction, None);
+
+		let auctions: Vec<Auction> = block_on(rpc.call(
+			"energy_listOpenAuctions",
+			(None::<u32>, 0u32, 10u32, None::<H256>, Some(Market::Intraday)),
+		))
+		.unwrap();
+		assert!(auctions.is_empty());
+
+		let history: Vec<(AuctionId, PartyType)> = block_on(rpc.call(
+			"energy_accountHistory",
+			(
+				AccountKeyring::Alice.to_account_id(),
+				0u32,
+				10u32,
+				None::<H256>,
+				Some(Market::Intraday),
+			),
+		))
+		.unwrap();
+		assert!(history.is_empty());
+	}
+
+	#[test]
+	fn list_open_auctions_should_page_and_filter() {
+		let rpc = rpc();
+
+		let auctions: Vec<Auction> =
+			block_on(rpc.call("energy_listOpenAuctions", (None::<u32>, 0u32, 10u32, None::<H256>)))
+				.unwrap();
+		assert_eq!(auctions, vec![test_auction(0, 1), test_auction(1, 2)]);
+
+		let auctions: Vec<Auction> =
+			block_on(rpc.call("energy_listOpenAuctions", (None::<u32>, 1u32, 1u32, None::<H256>)))
+				.unwrap();
+		assert_eq!(auctions, vec![test_auction(1, 2)]);
+
+		let auctions: Vec<Auction> =
+			block_on(rpc.call("energy_listOpenAuctions", (Some(2u32), 0u32, 10u32, None::<H256>)))
+				.unwrap();
+		assert_eq!(auctions, vec![test_auction(1, 2)]);
+	}
+
+	#[test]
+	fn account_history_should_work() {
+		let rpc = rpc();
+
+		let history: Vec<(AuctionId, PartyType)> = block_on(rpc.call(
+			"energy_accountHistory",
+			(AccountKeyring::Alice.to_account_id(), 0u32, 10u32, None::<H256>),
+		))
+		.unwrap();
+		assert_eq!(history, vec![(0, PartyType::Seller), (1, PartyType::Seller)]);
+
+		let history: Vec<(AuctionId, PartyType)> = block_on(rpc.call(
+			"energy_accountHistory",
+			(AccountKeyring::Bob.to_account_id(), 0u32, 10u32, None::<H256>),
+		))
+		.unwrap();
+		assert!(history.is_empty());
+	}
+
+	#[test]
+	fn quote_bid_should_report_runtime_errors() {
+		let rpc = rpc();
+
+		let quote: Option<BidQuote<Price, Balance>> =
+			block_on(rpc.call("energy_quoteBid", (0u64, 2_000u128, None::<H256>))).unwrap();
+		assert_eq!(
+			quote,
+			Some(BidQuote {
+				highest_bid: 1_000,
+				accepted: true,
+				reserved: 2_000,
+				fee: 20,
+				seller_receives: 1_980,
+			})
+		);
+
+		let quote: Result<Option<BidQuote<Price, Balance>>, _> =
+			block_on(rpc.call("energy_quoteBid", (7u64, 2_000u128, None::<H256>)));
+		assert!(quote.is_err());
+	}
+
+	#[test]
+	fn auction_events_should_be_decoded_and_filtered() {
+		let alice = AccountKeyring::Alice.to_account_id();
+		let bob = AccountKeyring::Bob.to_account_id();
+		let charlie = AccountKeyring::Charlie.to_account_id();
+
+		let record = |event| frame_system::EventRecord {
+			phase: frame_system::Phase::ApplyExtrinsic(0),
+			event,
+			topics: vec![],
+		};
+		let records = vec![
+			record(RuntimeEvent::DayAheadMarket(energy_bidding::Event::AuctionCreated {
+				auction_id: 0,
+				seller_id: alice.clone(),
+				energy_quantity: 2,
+				starting_price: 1_000,
+				seller_reputation: 0,
+			})),
+			record(RuntimeEvent::System(frame_system::Event::ExtrinsicSuccess {
+				dispatch_info: Default::default(),
+			})),
+			record(RuntimeEvent::IntradayMarket(energy_bidding::Event::AuctionBidAdded {
+				auction_id: 1,
+				seller_id: charlie.clone(),
+				energy_quantity: 2,
+				bid: Bid { bidder: bob.clone(), bid: 5_000 },
+			})),
+		];
+
+		let events = decode_auction_events(&records.encode());
+		assert_eq!(
+			events,
+			vec![
+				(
+					Market::DayAhead,
+					AuctionEvent::AuctionCreated {
+						auction_id: 0,
+						seller_id: alice.clone(),
+						energy_quantity: 2,
+						starting_price: 1_000,
+					},
+				),
+				(
+					Market::Intraday,
+					AuctionEvent::AuctionBidAdded {
+						auction_id: 1,
+						seller_id: charlie.clone(),
+						bidder: bob.clone(),
+						bid: 5_000,
+					},
+				),
+			]
+		);
+
+		assert_eq!(events[1].1.auction_id(), 1);
+		assert!(events[0].1.involves(&alice));
+		assert!(!events[0].1.involves(&bob));
+		assert!(events[1].1.involves(&bob));
+		assert!(events[1].1.involves(&charlie));
+
+		// events of an incompatible runtime are skipped
+		assert!(decode_auction_events(&[1, 2, 3]).is_empty());
+	}
+}
@@ -0,0 +1,78 @@
+//! Runtime API definition for the energy bidding market.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::{Codec, Decode, Encode};
+use energy_bidding::{AuctionData, Bid, BidQuote, PartyType, Tier};
+#[cfg(feature = "std")]
+use serde::{Deserialize, Serialize};
+use sp_std::vec::Vec;
+
+pub type AuctionDataFor<AccountId, AuctionId, Price, Quantity, BlockNumber> =
+    AuctionData<AccountId, AuctionId, Bid<AccountId, Price>, BlockNumber, Quantity, Tier>;
+
+/// The market instance a query is answered from.
+#[derive(Clone, Copy, Debug, Default, Encode, Decode, Eq, PartialEq)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+pub enum Market {
+    #[default]
+    DayAhead,
+    Intraday,
+}
+
+sp_api::decl_runtime_apis! {
+    /// Queries of the energy markets. Every method takes the `market` it is answered from.
+    ///
+    /// Only `open_auctions` leaves out private auctions. The API can't tell who is asking, and
+    /// the auctions are in public chain state anyway, so the lookups by id or account return
+    /// them as well.
+    pub trait EnergyBiddingApi<AccountId, AuctionId, Price, Quantity, BlockNumber, Balance>
+    where
+        AccountId: Codec,
+        AuctionId: Codec,
+        Price: Codec,
+        Quantity: Codec,
+        BlockNumber: Codec,
+        Balance: Codec,
+    {
+        /// A page of the public auctions still accepting bids, ordered by id. `tier` restricts
+        /// the page to auctions of that tier level.
+        fn open_auctions(
+            market: Market,
+            tier: Option<u32>,
+            page: u32,
+            page_size: u32,
+        ) -> Vec<AuctionDataFor<AccountId, AuctionId, Price, Quantity, BlockNumber>>;
+
+        /// The auction with the given id, if it is still listed.
+        fn auction(
+            market: Market,
+            auction_id: AuctionId,
+        ) -> Option<AuctionDataFor<AccountId, AuctionId, Price, Quantity, BlockNumber>>;
+
+        /// The best bid an account holds in each auction it is bidding in.
+        fn bids_of(market: Market, account: AccountId) -> Vec<(AuctionId, Bid<AccountId, Price>)>;
+
+        /// The current highest bid of an auction. The seller's starting bid until someone bids.
+        fn highest_bid(market: Market, auction_id: AuctionId) -> Option<Bid<AccountId, Price>>;
+
+        /// Blocks left until the auction closes, zero once it is waiting to be executed.
+        fn time_remaining(market: Market, auction_id: AuctionId) -> Option<BlockNumber>;
+
+        /// A page of the auctions an account took part in, oldest first.
+        fn account_history(
+            market: Market,
+            account: AccountId,
+            page: u32,
+            page_size: u32,
+        ) -> Vec<(AuctionId, PartyType)>;
+
+        /// What bidding `bid` on an auction would reserve, and the fee taken if the bid wins.
+        fn quote_bid(
+            market: Market,
+            auction_id: AuctionId,
+            bid: Price,
+        ) -> Option<BidQuote<Price, Balance>>;
+    }
+}
@@ -2,6 +2,16 @@
 
 pub use pallet::*;
 
+pub mod migrations;
+pub mod sponsorship;
+pub mod traits;
+pub mod weights;
+pub use sponsorship::SponsorMarketFees;
+pub use traits::{
+    EnergyMarket, OnAuctionCanceled, OnAuctionCreated, OnAuctionExecuted, PriceIndex,
+};
+pub use weights::WeightInfo;
+
 #[cfg(test)]
 mod mock;
 
@@ -11,17 +21,44 @@ mod tests;
 #[frame_support::pallet]
 pub mod pallet {
     use super::*;
-    use crate::pallet::sp_runtime::{traits::AtLeast32BitUnsigned, FixedPointOperand};
+    use crate::pallet::sp_runtime::{
+        traits::{
+            AccountIdConversion, AtLeast32BitUnsigned, One, SaturatedConversion, Saturating, Zero,
+        },
+        FixedPointOperand, Perbill,
+    };
     use frame_support::{
         dispatch::{fmt::Debug, Codec, EncodeLike},
         pallet_prelude::*,
         sp_runtime,
+        storage::with_storage_layer,
+        traits::{BalanceStatus, Currency, ExistenceRequirement, ReservableCurrency},
+        PalletId,
     };
     use frame_system::pallet_prelude::*;
     use scale_info::prelude::{vec, vec::Vec};
+    #[cfg(feature = "std")]
+    use serde::{Deserialize, Serialize};
 
     const STORAGE_VERSION: frame_support::traits::StorageVersion =
-        frame_support::traits::StorageVersion::new(1);
+        frame_support::traits::StorageVersion::new(2);
+
+    /// Points awarded to both parties of a completed trade.
+    pub const TRADE_POINTS: u32 = 10;
+    /// Points deducted from a seller cancelling an auction.
+    pub const CANCELLATION_PENALTY: u32 = 5;
+    /// Points deducted from the losing party of a dispute.
+    pub const DISPUTE_PENALTY: u32 = 50;
+    /// Decay periods after which a score is considered fully decayed.
+    const MAX_DECAY_PERIODS: u32 = 64;
+    /// Delivery periods in a day, each `Config::DeliveryPeriodLength` long.
+    pub const DELIVERY_PERIODS: u32 = 24;
+    const MINUTES_PER_DAY: u16 = 24 * 60;
+    /// Longest name of a buyer group, in bytes.
+    pub const MAX_GROUP_NAME_LEN: usize = 32;
+
+    pub type BalanceOf<T, I = ()> =
+        <<T as Config<I>>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
 
     #[pallet::pallet]
     #[pallet::without_storage_info]
@@ -73,14 +110,182 @@ pub mod pallet {
             + TypeInfo
             + FixedPointOperand
             + From<u128>;
+
+        /// The currency trades are settled in. Bids are reserved until the auction is executed.
+        type Currency: ReservableCurrency<Self::AccountId>;
+
+        /// The market treasury's id, used to derive the account collecting fees.
+        #[pallet::constant]
+        type PalletId: Get<PalletId>;
+
+        /// Fee taken from the cleared value of a trade, unless overridden for the tier.
+        #[pallet::constant]
+        type TradeFee: Get<Perbill>;
+
+        /// Fee paid by the seller when listing a new auction.
+        #[pallet::constant]
+        type ListingFee: Get<BalanceOf<Self, I>>;
+
+        /// Origin allowed to change the fee schedule and spend from the treasury.
+        type TreasuryOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+
+        /// Number of blocks after execution during which the buyer may dispute the trade. The
+        /// seller's payment stays reserved until the window has passed.
+        #[pallet::constant]
+        type DisputeWindow: Get<BlockNumberFor<Self>>;
+
+        /// Origin allowed to resolve disputes, e.g. a collective of arbitrators.
+        type ArbitratorOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+
+        /// Share of a reputation score that is kept after each decay period.
+        #[pallet::constant]
+        type ReputationRetention: Get<Perbill>;
+
+        /// Number of blocks after which a reputation score decays by `ReputationRetention`.
+        #[pallet::constant]
+        type ReputationDecayPeriod: Get<BlockNumberFor<Self>>;
+
+        /// Maximum number of bids an account may hold in one auction.
+        #[pallet::constant]
+        type MaxBidsPerAuction: Get<u32>;
+
+        /// Maximum number of bids an account may hold across all running auctions.
+        #[pallet::constant]
+        type MaxLiveBids: Get<u32>;
+
+        /// Maximum number of auto bids set on one auction.
+        #[pallet::constant]
+        type MaxAutoBids: Get<u32>;
+
+        /// Maximum number of accounts in a buyer group or in the allow-list of an auction.
+        #[pallet::constant]
+        type MaxGroupMembers: Get<u32>;
+
+        /// Deposit reserved for every bid. It is refunded once the bidder has no bid left in the
+        /// auction or the auction closes.
+        #[pallet::constant]
+        type BidDeposit: Get<BalanceOf<Self, I>>;
+
+        /// Share of the highest bid paid to the treasury when it is retracted.
+        #[pallet::constant]
+        type RetractionPenalty: Get<Perbill>;
+
+        /// Number of blocks an auction's settlement is kept after execution. Zero keeps
+        /// settlements forever.
+        #[pallet::constant]
+        type SettlementRetention: Get<BlockNumberFor<Self>>;
+
+        /// Deposit reserved from the seller while an auction is listed.
+        #[pallet::constant]
+        type ListingDeposit: Get<BalanceOf<Self, I>>;
+
+        /// Reduction of the starting price each time an unsold auction is relisted.
+        #[pallet::constant]
+        type RelistDiscount: Get<Perbill>;
+
+        /// Maximum number of times an unsold auction may be relisted automatically.
+        #[pallet::constant]
+        type MaxRelistRounds: Get<u32>;
+
+        /// Longest auction period a seller may choose, in minutes.
+        #[pallet::constant]
+        type MaxAuctionPeriod: Get<u16>;
+
+        /// Quantity in kWh from which an auction is listed in tier 2 instead of tier 1.
+        #[pallet::constant]
+        type LargeTierThreshold: Get<u128>;
+
+        /// Maximum number of ended auctions, escrow releases and settlement prunes handled in
+        /// one block. Whatever is left over is carried into the following blocks.
+        #[pallet::constant]
+        type MaxProcessedPerBlock: Get<u32>;
+
+        /// Maximum number of past blocks whose queues are scanned in one block, so catching up
+        /// after a halt or a stall is spread over several blocks.
+        #[pallet::constant]
+        type MaxBlocksScannedPerBlock: Get<u32>;
+
+        /// Length of a delivery period in blocks, i.e. an hour. An auction delivers in the
+        /// period its end falls in when it is listed.
+        #[pallet::constant]
+        type DeliveryPeriodLength: Get<BlockNumberFor<Self>>;
+
+        /// Maximum number of listings or bids in one batch call.
+        #[pallet::constant]
+        type MaxBatchSize: Get<u32>;
+
+        /// Number of past trades kept per tier for the price index.
+        #[pallet::constant]
+        type MaxPriceObservations: Get<u32>;
+
+        /// Number of blocks the stored price index averages over.
+        #[pallet::constant]
+        type PriceIndexWindow: Get<BlockNumberFor<Self>>;
+
+        /// Origin allowed to set the administrative price limits.
+        type RegulatorOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+
+        /// Origin allowed to halt and resume trading, e.g. the grid regulator.
+        type PauseOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+
+        /// Largest deviation of a clearing unit price from the tier's time-weighted index before
+        /// trading is halted automatically. Zero disables the circuit breaker.
+        #[pallet::constant]
+        type CircuitBreakerThreshold: Get<Perbill>;
+
+        /// Number of blocks over which a small prosumer's sponsored calls are counted.
+        #[pallet::constant]
+        type SponsorshipPeriod: Get<BlockNumberFor<Self>>;
+
+        /// Market calls per `SponsorshipPeriod` whose fees are waived for a small prosumer.
+        #[pallet::constant]
+        type SponsoredCallsPerPeriod: Get<u32>;
+
+        /// Failed sponsored calls after which a small prosumer loses its sponsorship.
+        #[pallet::constant]
+        type MaxSponsoredFailures: Get<u32>;
+
+        /// Handler notified when an auction is listed.
+        type OnAuctionCreated: OnAuctionCreated<
+            Self::AccountId,
+            Self::AuctionId,
+            Self::Quantity,
+            Self::Price,
+        >;
+
+        /// Handler notified when a seller cancels an auction.
+        type OnAuctionCanceled: OnAuctionCanceled<
+            Self::AccountId,
+            Self::AuctionId,
+            Self::Quantity,
+            Self::Price,
+        >;
+
+        /// Upper bound on the weight of one `OnAuctionCreated` or `OnAuctionCanceled` call,
+        /// charged up front by the calls that list or cancel auctions.
+        #[pallet::constant]
+        type MaxMarketHookWeight: Get<Weight>;
+
+        /// Handler notified of every executed trade, e.g. for loyalty or grid accounting.
+        type OnAuctionExecuted: OnAuctionExecuted<
+            Self::AccountId,
+            Self::AuctionId,
+            Self::Quantity,
+            Self::Price,
+        >;
+
+        /// Weight information for the operations of this pallet.
+        type WeightInfo: WeightInfo;
     }
 
+    #[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
     #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
     pub struct Bid<AccountId, Price> {
         pub bidder: AccountId,
         pub bid: Price,
     }
 
+    #[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
     #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
     pub enum AuctionStatus {
         Open,
@@ -92,6 +297,24 @@ pub mod pallet {
         }
     }
 
+    /// What the market currently accepts.
+    #[derive(Clone, Copy, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+    pub enum MarketStatus {
+        Open,
+        /// Bids on listed auctions are accepted, new listings and amendments are not.
+        BidsOnly,
+        /// Nothing is accepted, not even cancellations, and ended auctions are not processed.
+        /// The book stays frozen until trading resumes, and the auctions still running get
+        /// back the time they were halted for.
+        Halted,
+    }
+    impl Default for MarketStatus {
+        fn default() -> Self {
+            MarketStatus::Open
+        }
+    }
+
+    #[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
     #[derive(Clone, Encode, Decode, Default, Eq, PartialEq, RuntimeDebug, TypeInfo)]
     pub struct AuctionData<AccountId, AuctionId, Bid, BlockNumber, Quantity, Tier> {
         pub auction_id: AuctionId,
@@ -105,8 +328,12 @@ pub mod pallet {
         pub end_at: BlockNumber,
         pub highest_bid: Bid,
         pub auction_category: Tier,
+        pub min_bidder_reputation: Option<u32>,
+        /// The hour of the day the energy is delivered in, fixed when the auction is listed.
+        pub delivery_period: u32,
     }
 
+    #[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
     #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
     pub struct Tier {
         pub level: u32,
@@ -117,6 +344,7 @@ pub mod pallet {
         }
     }
 
+    #[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
     #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
     pub enum PartyType {
         Seller,
@@ -132,48 +360,222 @@ pub mod pallet {
     pub struct AuctionInfo<AccountId, AuctionId, Bid, BlockNumber, Tier, PartyType, Quantity> {
         pub participant_id: Option<AccountId>,
         pub party_type: PartyType,
-        pub auctions: Vec<AuctionData<AccountId, AuctionId, Bid, BlockNumber, Quantity, Tier>>, /* Maximum* length of 5 */
+        pub auctions: Vec<AuctionData<AccountId, AuctionId, Bid, BlockNumber, Quantity, Tier>>,
+    }
+
+    pub type AuctionDataOf<T, I = ()> = AuctionData<
+        <T as frame_system::Config>::AccountId,
+        <T as Config<I>>::AuctionId,
+        Bid<<T as frame_system::Config>::AccountId, <T as Config<I>>::Price>,
+        BlockNumberFor<T>,
+        <T as Config<I>>::Quantity,
+        Tier,
+    >;
+
+    pub type AuctionInfoOf<T, I = ()> = AuctionInfo<
+        <T as frame_system::Config>::AccountId,
+        <T as Config<I>>::AuctionId,
+        Bid<<T as frame_system::Config>::AccountId, <T as Config<I>>::Price>,
+        BlockNumberFor<T>,
+        Tier,
+        PartyType,
+        <T as Config<I>>::Quantity,
+    >;
+
+    /// Administrative limits on the price per kWh.
+    #[derive(Clone, Encode, Decode, Default, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+    pub struct PriceLimit<Price> {
+        pub floor: Option<Price>,
+        pub cap: Option<Price>,
+    }
+
+    /// A standing instruction to raise a buyer's bid by `step` whenever it is outbid, up to
+    /// `max_price`.
+    #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+    pub struct AutoBid<Price, BlockNumber> {
+        pub max_price: Price,
+        pub step: Price,
+        /// Block and extrinsic index the auto bid was set at. Of two equal maximums, the
+        /// earlier one wins.
+        pub set_at: (BlockNumber, u32),
+    }
+
+    /// Fee sponsorship of a registered small prosumer.
+    #[derive(Clone, Encode, Decode, Default, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+    pub struct Sponsorship<BlockNumber> {
+        /// Start of the current sponsorship period.
+        pub period_start: BlockNumber,
+        /// Sponsored calls made in the current period.
+        pub used: u32,
+        /// Sponsored calls that failed since registration.
+        pub failures: u32,
+    }
+
+    #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+    pub struct Settlement<AccountId, Price, Quantity, Balance, BlockNumber> {
+        pub seller_id: AccountId,
+        pub buyer_id: AccountId,
+        pub quantity: Quantity,
+        pub clearing_price: Price,
+        pub fee: Balance,
+        pub auction_category: Tier,
+        pub executed_at: BlockNumber,
+    }
+
+    /// One listing of a `new_batch` call or a schedule, with the same parameters as `new`.
+    #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+    pub struct ListingSpec<AccountId> {
+        pub energy_quantity: u128,
+        pub starting_price: u128,
+        pub auction_period: u16,
+        pub min_bidder_reputation: Option<u32>,
+        pub buyers: Option<BuyerAccess<AccountId>>,
+    }
+
+    /// Who may bid on a private auction.
+    #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+    pub enum BuyerAccess<AccountId> {
+        /// Only the listed accounts.
+        AllowList(Vec<AccountId>),
+        /// Only the members of the seller's buyer group of that name, as it is when bidding.
+        Group(Vec<u8>),
+    }
+
+    /// When a schedule lists its next auction.
+    #[derive(Clone, Copy, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+    pub enum Recurrence<BlockNumber> {
+        /// Every given number of blocks.
+        Every(BlockNumber),
+        /// Once a day, at the given minute of the day.
+        DailyAt(u16),
+    }
+
+    /// A listing repeated by the pallet until the seller cancels it or its deposit runs out.
+    #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+    pub struct Schedule<AccountId, Balance, BlockNumber> {
+        pub seller_id: AccountId,
+        pub listing: ListingSpec<AccountId>,
+        pub recurrence: Recurrence<BlockNumber>,
+        /// Reserved balance left to the schedule. Every auction it lists pays its listing fee
+        /// from it.
+        pub deposit: Balance,
+        pub next_run: BlockNumber,
+    }
+
+    /// What a bid would reserve from the bidder and pay out if it wins.
+    #[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+    #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+    pub struct BidQuote<Price, Balance> {
+        pub highest_bid: Price,
+        pub accepted: bool,
+        pub reserved: Balance,
+        pub fee: Balance,
+        pub seller_receives: Balance,
+    }
+
+    /// A trade recorded in its tier's price history.
+    #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+    pub struct PriceObservation<Price, Quantity, BlockNumber> {
+        pub clearing_price: Price,
+        pub quantity: Quantity,
+        pub executed_at: BlockNumber,
+    }
+
+    /// Average unit prices of a tier over the last `PriceIndexWindow` blocks.
+    #[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+    #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+    pub struct PriceIndexValue<Price, BlockNumber> {
+        pub time_weighted: Price,
+        pub volume_weighted: Price,
+        pub updated_at: BlockNumber,
+    }
+
+    #[derive(Clone, Encode, Decode, Default, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+    pub struct Reputation<Quantity, BlockNumber> {
+        pub completed_trades: u32,
+        pub contracted_quantity: Quantity,
+        pub delivered_quantity: Quantity,
+        pub cancellations: u32,
+        pub lost_disputes: u32,
+        pub score: u32,
+        pub updated_at: BlockNumber,
+    }
+
+    #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+    pub struct Escrow<AccountId, Balance, BlockNumber, Quantity> {
+        pub seller_id: AccountId,
+        pub buyer_id: AccountId,
+        pub quantity: Quantity,
+        pub amount: Balance,
+        pub executed_at: BlockNumber,
+        pub release_at: BlockNumber,
+    }
+
+    #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+    pub struct Dispute<AccountId, Balance, BlockNumber, Quantity> {
+        pub seller_id: AccountId,
+        pub buyer_id: AccountId,
+        pub quantity: Quantity,
+        pub amount: Balance,
+        pub executed_at: BlockNumber,
+        pub raised_at: BlockNumber,
+        pub resolved_at: Option<BlockNumber>,
+        pub buyer_share: Option<Perbill>,
     }
 
     #[pallet::storage]
     #[pallet::getter(fn auctions_index)]
     pub(super) type AuctionIndex<T: Config<I>, I: 'static = ()> = StorageValue<_, T::AuctionId>;
 
+    /// Open auctions listed by an account, keyed by (seller, auction id).
     #[pallet::storage]
-    #[pallet::getter(fn auctions_of)]
-    pub(super) type AuctionsOf<T: Config<I>, I: 'static = ()> = StorageMap<
+    #[pallet::getter(fn seller_auctions)]
+    pub(super) type SellerAuctions<T: Config<I>, I: 'static = ()> = StorageDoubleMap<
         _,
         Twox64Concat,
         T::AccountId,
-        AuctionInfo<
-            T::AccountId,
-            T::AuctionId,
-            Bid<T::AccountId, T::Price>,
-            BlockNumberFor<T>,
-            Tier,
-            PartyType,
-            T::Quantity,
-        >,
+        Twox64Concat,
+        T::AuctionId,
+        (),
         OptionQuery,
     >;
 
+    /// Open auctions an account holds a bid in, keyed by (buyer, auction id).
     #[pallet::storage]
-    #[pallet::getter(fn auctions)]
-    pub(super) type Auctions<T: Config<I>, I: 'static = ()> = StorageMap<
+    #[pallet::getter(fn buyer_auctions)]
+    pub(super) type BuyerAuctions<T: Config<I>, I: 'static = ()> = StorageDoubleMap<
         _,
         Twox64Concat,
+        T::AccountId,
+        Twox64Concat,
         T::AuctionId,
-        AuctionData<
-            T::AccountId,
-            T::AuctionId,
-            Bid<T::AccountId, T::Price>,
-            BlockNumberFor<T>,
-            T::Quantity,
-            Tier,
-        >,
+        (),
+        OptionQuery,
+    >;
+
+    /// Every auction an account took part in, in the order it joined them.
+    #[pallet::storage]
+    #[pallet::getter(fn auction_history)]
+    pub(super) type AuctionHistory<T: Config<I>, I: 'static = ()> = StorageDoubleMap<
+        _,
+        Twox64Concat,
+        T::AccountId,
+        Twox64Concat,
+        u32,
+        (T::AuctionId, PartyType),
         OptionQuery,
     >;
 
+    #[pallet::storage]
+    #[pallet::getter(fn auction_history_count)]
+    pub(super) type AuctionHistoryCount<T: Config<I>, I: 'static = ()> =
+        StorageMap<_, Twox64Concat, T::AccountId, u32, ValueQuery>;
+
+    #[pallet::storage]
+    #[pallet::getter(fn auctions)]
+    pub(super) type Auctions<T: Config<I>, I: 'static = ()> =
+        StorageMap<_, Twox64Concat, T::AuctionId, AuctionDataOf<T, I>, OptionQuery>;
+
     #[pallet::storage]
     #[pallet::getter(fn auction_execution_queue)]
     pub(super) type AuctionsExecutionQueue<T: Config<I>, I: 'static = ()> = StorageDoubleMap<
@@ -186,6 +588,233 @@ pub mod pallet {
         OptionQuery,
     >;
 
+    /// The oldest block whose queued work has not been fully processed yet.
+    #[pallet::storage]
+    #[pallet::getter(fn expiry_cursor)]
+    pub(super) type ExpiryCursor<T: Config<I>, I: 'static = ()> =
+        StorageValue<_, BlockNumberFor<T>, OptionQuery>;
+
+    #[pallet::storage]
+    #[pallet::getter(fn market_status)]
+    pub(super) type MarketState<T: Config<I>, I: 'static = ()> =
+        StorageValue<_, MarketStatus, ValueQuery>;
+
+    /// The block at which trading was halted, while it is.
+    #[pallet::storage]
+    #[pallet::getter(fn halted_since)]
+    pub(super) type HaltedSince<T: Config<I>, I: 'static = ()> =
+        StorageValue<_, BlockNumberFor<T>, OptionQuery>;
+
+    #[pallet::storage]
+    #[pallet::getter(fn tier_trade_fee)]
+    pub(super) type TierTradeFee<T: Config<I>, I: 'static = ()> =
+        StorageMap<_, Twox64Concat, u32, Perbill, OptionQuery>;
+
+    /// Price limits applying to every delivery period of a tier.
+    #[pallet::storage]
+    #[pallet::getter(fn tier_price_limits)]
+    pub(super) type TierPriceLimits<T: Config<I>, I: 'static = ()> =
+        StorageMap<_, Twox64Concat, u32, PriceLimit<T::Price>, OptionQuery>;
+
+    /// Price limits of a single delivery period of a tier, replacing the tier's limits.
+    #[pallet::storage]
+    #[pallet::getter(fn period_price_limits)]
+    pub(super) type PeriodPriceLimits<T: Config<I>, I: 'static = ()> = StorageDoubleMap<
+        _,
+        Twox64Concat,
+        u32,
+        Twox64Concat,
+        u32,
+        PriceLimit<T::Price>,
+        OptionQuery,
+    >;
+
+    #[pallet::storage]
+    #[pallet::getter(fn listing_deposits)]
+    pub(super) type ListingDeposits<T: Config<I>, I: 'static = ()> =
+        StorageMap<_, Twox64Concat, T::AuctionId, BalanceOf<T, I>, OptionQuery>;
+
+    /// Bid deposits held per auction and bidder.
+    #[pallet::storage]
+    #[pallet::getter(fn bid_deposits)]
+    pub(super) type BidDeposits<T: Config<I>, I: 'static = ()> = StorageDoubleMap<
+        _,
+        Twox64Concat,
+        T::AuctionId,
+        Twox64Concat,
+        T::AccountId,
+        BalanceOf<T, I>,
+        OptionQuery,
+    >;
+
+    /// Number of bids an account holds in auctions that are still running.
+    #[pallet::storage]
+    #[pallet::getter(fn live_bids)]
+    pub(super) type LiveBids<T: Config<I>, I: 'static = ()> =
+        StorageMap<_, Twox64Concat, T::AccountId, u32, ValueQuery>;
+
+    /// Auto bids per auction and bidder. The maximum stays out of events and the runtime API,
+    /// but like all chain state it can be read from storage.
+    #[pallet::storage]
+    #[pallet::getter(fn auto_bids)]
+    pub(super) type AutoBids<T: Config<I>, I: 'static = ()> = StorageDoubleMap<
+        _,
+        Twox64Concat,
+        T::AuctionId,
+        Twox64Concat,
+        T::AccountId,
+        AutoBid<T::Price, BlockNumberFor<T>>,
+        OptionQuery,
+    >;
+
+    #[pallet::storage]
+    #[pallet::getter(fn schedule_index)]
+    pub(super) type ScheduleIndex<T: Config<I>, I: 'static = ()> = StorageValue<_, u32, ValueQuery>;
+
+    #[pallet::storage]
+    #[pallet::getter(fn schedules)]
+    pub(super) type Schedules<T: Config<I>, I: 'static = ()> = StorageMap<
+        _,
+        Twox64Concat,
+        u32,
+        Schedule<T::AccountId, BalanceOf<T, I>, BlockNumberFor<T>>,
+        OptionQuery,
+    >;
+
+    /// Schedules by the block of their next run.
+    #[pallet::storage]
+    #[pallet::getter(fn schedule_queue)]
+    pub(super) type ScheduleQueue<T: Config<I>, I: 'static = ()> =
+        StorageDoubleMap<_, Twox64Concat, BlockNumberFor<T>, Twox64Concat, u32, (), OptionQuery>;
+
+    /// Named groups of pre-qualified buyers, per owning seller.
+    #[pallet::storage]
+    #[pallet::getter(fn buyer_groups)]
+    pub(super) type BuyerGroups<T: Config<I>, I: 'static = ()> = StorageDoubleMap<
+        _,
+        Twox64Concat,
+        T::AccountId,
+        Blake2_128Concat,
+        Vec<u8>,
+        Vec<T::AccountId>,
+        OptionQuery,
+    >;
+
+    /// Buyers allowed to bid on private auctions. Auctions without an entry are public. Private
+    /// auctions are only left out of `open_auctions`: the other queries look auctions up by id
+    /// or account, and return them like any other state of the chain.
+    #[pallet::storage]
+    #[pallet::getter(fn auction_access)]
+    pub(super) type AuctionAccess<T: Config<I>, I: 'static = ()> =
+        StorageMap<_, Twox64Concat, T::AuctionId, BuyerAccess<T::AccountId>, OptionQuery>;
+
+    /// Small prosumers whose market calls are fee-less within their quota.
+    #[pallet::storage]
+    #[pallet::getter(fn small_prosumers)]
+    pub(super) type SmallProsumers<T: Config<I>, I: 'static = ()> =
+        StorageMap<_, Twox64Concat, T::AccountId, Sponsorship<BlockNumberFor<T>>, OptionQuery>;
+
+    /// Remaining automatic relist rounds of an auction, set by its seller.
+    #[pallet::storage]
+    #[pallet::getter(fn relist_rounds)]
+    pub(super) type RelistRounds<T: Config<I>, I: 'static = ()> =
+        StorageMap<_, Twox64Concat, T::AuctionId, u32, OptionQuery>;
+
+    /// Final outcome of executed auctions, kept for `SettlementRetention` blocks.
+    #[pallet::storage]
+    #[pallet::getter(fn settlements)]
+    pub(super) type Settlements<T: Config<I>, I: 'static = ()> = StorageMap<
+        _,
+        Twox64Concat,
+        T::AuctionId,
+        Settlement<T::AccountId, T::Price, T::Quantity, BalanceOf<T, I>, BlockNumberFor<T>>,
+        OptionQuery,
+    >;
+
+    #[pallet::storage]
+    #[pallet::getter(fn settlement_pruning_queue)]
+    pub(super) type SettlementPruningQueue<T: Config<I>, I: 'static = ()> = StorageDoubleMap<
+        _,
+        Twox64Concat,
+        BlockNumberFor<T>,
+        Blake2_128Concat,
+        T::AuctionId,
+        (),
+        OptionQuery,
+    >;
+
+    /// Ring buffer of the latest trades of each tier. Once full, the oldest trade is overwritten.
+    #[pallet::storage]
+    #[pallet::getter(fn price_observations)]
+    pub(super) type PriceObservations<T: Config<I>, I: 'static = ()> = StorageMap<
+        _,
+        Twox64Concat,
+        u32,
+        BoundedVec<
+            PriceObservation<T::Price, T::Quantity, BlockNumberFor<T>>,
+            T::MaxPriceObservations,
+        >,
+        ValueQuery,
+    >;
+
+    /// Slot of a tier's ring buffer holding its oldest trade.
+    #[pallet::storage]
+    #[pallet::getter(fn price_observation_cursor)]
+    pub(super) type PriceObservationCursor<T: Config<I>, I: 'static = ()> =
+        StorageMap<_, Twox64Concat, u32, u32, ValueQuery>;
+
+    #[pallet::storage]
+    #[pallet::getter(fn price_indices)]
+    pub(super) type PriceIndices<T: Config<I>, I: 'static = ()> =
+        StorageMap<_, Twox64Concat, u32, PriceIndexValue<T::Price, BlockNumberFor<T>>, OptionQuery>;
+
+    #[pallet::storage]
+    #[pallet::getter(fn escrows)]
+    pub(super) type Escrows<T: Config<I>, I: 'static = ()> = StorageMap<
+        _,
+        Twox64Concat,
+        T::AuctionId,
+        Escrow<T::AccountId, BalanceOf<T, I>, BlockNumberFor<T>, T::Quantity>,
+        OptionQuery,
+    >;
+
+    #[pallet::storage]
+    #[pallet::getter(fn escrow_release_queue)]
+    pub(super) type EscrowReleaseQueue<T: Config<I>, I: 'static = ()> = StorageDoubleMap<
+        _,
+        Twox64Concat,
+        BlockNumberFor<T>,
+        Blake2_128Concat,
+        T::AuctionId,
+        (),
+        OptionQuery,
+    >;
+
+    #[pallet::storage]
+    #[pallet::getter(fn disputes)]
+    pub(super) type Disputes<T: Config<I>, I: 'static = ()> = StorageMap<
+        _,
+        Twox64Concat,
+        T::AuctionId,
+        Dispute<T::AccountId, BalanceOf<T, I>, BlockNumberFor<T>, T::Quantity>,
+        OptionQuery,
+    >;
+
+    #[pallet::storage]
+    #[pallet::getter(fn reputations)]
+    pub(super) type Reputations<T: Config<I>, I: 'static = ()> = StorageMap<
+        _,
+        Twox64Concat,
+        T::AccountId,
+        Reputation<T::Quantity, BlockNumberFor<T>>,
+        ValueQuery,
+    >;
+
+    #[pallet::storage]
+    #[pallet::getter(fn min_seller_reputation)]
+    pub(super) type MinSellerReputation<T: Config<I>, I: 'static = ()> =
+        StorageMap<_, Twox64Concat, T::AccountId, u32, OptionQuery>;
+
     #[pallet::genesis_config]
     pub struct GenesisConfig<T: Config<I>, I: 'static = ()> {
         pub auction_index: T::AuctionId,
@@ -193,33 +822,51 @@ pub mod pallet {
 
     impl<T: Config<I>, I: 'static> Default for GenesisConfig<T, I> {
         fn default() -> Self {
-            Self {
-                auction_index: Default::default(),
-            }
+            Self { auction_index: Default::default() }
         }
     }
 
-	use frame_support::traits::BuildGenesisConfig;
+    use frame_support::traits::BuildGenesisConfig;
     #[pallet::genesis_build]
     impl<T: Config<I>, I: 'static> BuildGenesisConfig for GenesisConfig<T, I> {
         fn build(&self) {
             let initial_id = self.auction_index;
             <AuctionIndex<T, I>>::put(initial_id);
+
+            <Pallet<T, I>>::ensure_treasury();
         }
     }
 
     #[pallet::hooks]
     impl<T: Config<I>, I: 'static> Hooks<BlockNumberFor<T>> for Pallet<T, I> {
-        fn on_initialize(_now: BlockNumberFor<T>) -> Weight {
-            Weight::from_all(100_000_000u64)
-        }
-
-        fn on_finalize(now: BlockNumberFor<T>) {
-            for (auction_id, _) in AuctionsExecutionQueue::<T, I>::drain_prefix(now) {
-                if let Some(auction) = Auctions::<T, I>::get(auction_id) {
-                    Self::on_auction_ended(auction.auction_id);
+        /// Processes the work queued for previous blocks, at most `MaxProcessedPerBlock` items
+        /// from at most `MaxBlocksScannedPerBlock` blocks. An auction ending at block `n` is
+        /// executed at the start of block `n + 1` at the earliest, so bids included in its last
+        /// block still count.
+        fn on_initialize(now: BlockNumberFor<T>) -> Weight {
+            let mut weight = T::WeightInfo::on_initialize_base();
+            if Self::is_halted() {
+                return weight;
+            }
+            let mut budget = T::MaxProcessedPerBlock::get();
+            let mut scans_left = T::MaxBlocksScannedPerBlock::get();
+
+            let mut block =
+                ExpiryCursor::<T, I>::get().unwrap_or_else(|| now.saturating_sub(One::one()));
+
+            while block < now && scans_left > 0 {
+                scans_left -= 1;
+                weight.saturating_accrue(T::WeightInfo::scan_queue().saturating_mul(4));
+                weight.saturating_accrue(Self::process_due(block, &mut budget));
+                if budget == 0 || Self::is_halted() {
+                    break;
                 }
+                block = block.saturating_add(One::one());
             }
+
+            ExpiryCursor::<T, I>::put(block);
+
+            weight
         }
     }
 
@@ -231,6 +878,7 @@ pub mod pallet {
             seller_id: T::AccountId,
             energy_quantity: T::Quantity,
             starting_price: T::Price,
+            seller_reputation: u32,
         },
 
         AuctionBidAdded {
@@ -256,6 +904,7 @@ pub mod pallet {
             energy_quantity: T::Quantity,
             starting_price: T::Price,
             highest_bid: T::Price,
+            fee: BalanceOf<T, I>,
             executed_at: BlockNumberFor<T>,
         },
 
@@ -265,280 +914,2302 @@ pub mod pallet {
             energy_quantity: T::Quantity,
             starting_price: T::Price,
         },
-    }
-
-    //////////////////////
-    // Pallet errors   //
-    /////////////////////
-    // Errors inform users that something went wrong.
-    #[pallet::error]
-    pub enum Error<T, I = ()> {
-        AuctionDoesNotExist,
 
-        AuctionIsOver,
+        TradeFeeSet {
+            tier_level: u32,
+            fee: Option<Perbill>,
+        },
 
-        InsuffficientAttachedDeposit,
-    }
+        TreasurySpent {
+            beneficiary: T::AccountId,
+            amount: BalanceOf<T, I>,
+        },
 
-    #[pallet::call]
-    impl<T: Config<I>, I: 'static> Pallet<T, I> {
-        #[pallet::call_index(0)]
-        #[pallet::weight(100_000_000)]
-        pub fn new(
-            origin: OriginFor<T>,
-            energy_quantity: u128, // in KWH
-            starting_price: u128,  // in parachain native token
-            auction_period: u16,   // in minutes
-        ) -> DispatchResult {
-            let seller = ensure_signed(origin)?;
+        EscrowReleased {
+            auction_id: T::AuctionId,
+            seller_id: T::AccountId,
+            amount: BalanceOf<T, I>,
+        },
+
+        /// The buyer's reserve could not cover the whole winning bid. Only what was reserved
+        /// was paid to the seller and the treasury.
+        SettlementShortfall {
+            auction_id: T::AuctionId,
+            buyer_id: T::AccountId,
+            shortfall: BalanceOf<T, I>,
+        },
+
+        DisputeRaised {
+            auction_id: T::AuctionId,
+            buyer_id: T::AccountId,
+            seller_id: T::AccountId,
+            amount: BalanceOf<T, I>,
+        },
+
+        DisputeResolved {
+            auction_id: T::AuctionId,
+            buyer_id: T::AccountId,
+            seller_id: T::AccountId,
+            to_buyer: BalanceOf<T, I>,
+            to_seller: BalanceOf<T, I>,
+        },
+
+        MinSellerReputationSet {
+            who: T::AccountId,
+            min_reputation: Option<u32>,
+        },
+
+        BidRetracted {
+            auction_id: T::AuctionId,
+            bidder: T::AccountId,
+            penalty: BalanceOf<T, I>,
+            highest_bid: Bid<T::AccountId, T::Price>,
+        },
+
+        AuctionAmended {
+            auction_id: T::AuctionId,
+            seller_id: T::AccountId,
+            energy_quantity: T::Quantity,
+            starting_price: T::Price,
+            end_at: BlockNumberFor<T>,
+        },
+
+        AuctionExpired {
+            auction_id: T::AuctionId,
+            seller_id: T::AccountId,
+            energy_quantity: T::Quantity,
+            starting_price: T::Price,
+            expired_at: BlockNumberFor<T>,
+        },
+
+        AuctionRelisted {
+            auction_id: T::AuctionId,
+            seller_id: T::AccountId,
+            energy_quantity: T::Quantity,
+            starting_price: T::Price,
+            end_at: BlockNumberFor<T>,
+            rounds_left: u32,
+        },
+
+        AutoRelistSet {
+            auction_id: T::AuctionId,
+            rounds: u32,
+        },
+
+        MarketStatusChanged {
+            status: MarketStatus,
+        },
+
+        CircuitBreakerTripped {
+            auction_id: T::AuctionId,
+            tier_level: u32,
+            unit_price: T::Price,
+            reference_price: T::Price,
+        },
+
+        PriceLimitsUpdated {
+            tier_level: u32,
+            delivery_period: Option<u32>,
+            floor: Option<T::Price>,
+            cap: Option<T::Price>,
+        },
+
+        ProsumerRegistered {
+            who: T::AccountId,
+        },
+
+        ProsumerDeregistered {
+            who: T::AccountId,
+        },
+
+        /// The fee of a market call was waived. `used` counts the sponsored calls of the period.
+        FeeSponsored {
+            who: T::AccountId,
+            used: u32,
+        },
+
+        SponsorshipRevoked {
+            who: T::AccountId,
+            failures: u32,
+        },
+
+        AutoBidSet {
+            auction_id: T::AuctionId,
+            bidder: T::AccountId,
+        },
+
+        AutoBidCanceled {
+            auction_id: T::AuctionId,
+            bidder: T::AccountId,
+        },
+
+        /// The auto bid was outbid beyond its maximum.
+        AutoBidExhausted {
+            auction_id: T::AuctionId,
+            bidder: T::AccountId,
+        },
+
+        /// The auto bid was dropped because its bid could not be placed.
+        AutoBidFailed {
+            auction_id: T::AuctionId,
+            bidder: T::AccountId,
+            error: DispatchError,
+        },
+
+        BuyerGroupUpdated {
+            owner: T::AccountId,
+            name: Vec<u8>,
+            members: u32,
+        },
+
+        BuyerGroupRemoved {
+            owner: T::AccountId,
+            name: Vec<u8>,
+        },
+
+        ScheduleCreated {
+            schedule_id: u32,
+            seller_id: T::AccountId,
+            next_run: BlockNumberFor<T>,
+        },
+
+        ScheduleRun {
+            schedule_id: u32,
+            auction_id: T::AuctionId,
+        },
+
+        /// The schedule could not list its auction this time. It stays in place for its next
+        /// run.
+        ScheduleRunFailed {
+            schedule_id: u32,
+            error: DispatchError,
+        },
+
+        ScheduleCanceled {
+            schedule_id: u32,
+        },
+
+        /// The schedule's deposit can't pay another listing fee. The rest was returned.
+        ScheduleExhausted {
+            schedule_id: u32,
+        },
+
+        /// A `new_batch` call listed its auctions, in the order of the listings.
+        ListingBatchCreated {
+            seller_id: T::AccountId,
+            auction_ids: Vec<T::AuctionId>,
+        },
+
+        /// A `bid_batch` call placed its bids. Holds the highest bid of each auction after the
+        /// bid, in the order of the bids.
+        BidBatchPlaced {
+            bidder: T::AccountId,
+            highest_bids: Vec<(T::AuctionId, Bid<T::AccountId, T::Price>)>,
+        },
+    }
+
+    //////////////////////
+    // Pallet errors   //
+    /////////////////////
+    // Errors inform users that something went wrong.
+    #[pallet::error]
+    pub enum Error<T, I = ()> {
+        AuctionDoesNotExist,
+
+        AuctionIsOver,
+
+        InsuffficientAttachedDeposit,
+
+        InsufficientTreasuryBalance,
+
+        EscrowDoesNotExist,
+
+        NotBuyer,
+
+        DisputeWindowClosed,
+
+        DisputeAlreadyRaised,
+
+        DisputeDoesNotExist,
+
+        DisputeAlreadyResolved,
+
+        NotSeller,
+
+        BidderReputationTooLow,
+
+        SellerReputationTooLow,
+
+        SellerCannotBid,
+
+        BidDoesNotExist,
+
+        AuctionHasBids,
+
+        InvalidAuctionPeriod,
+
+        TooManyRelistRounds,
+
+        MarketNotOpen,
+
+        MarketHalted,
+
+        InvalidPriceLimits,
+
+        InvalidDeliveryPeriod,
+
+        StartingPriceBelowFloor,
+
+        StartingPriceAboveCap,
+
+        BidBelowFloor,
+
+        BidAboveCap,
+
+        BidTooLow,
+
+        TooManyBidsInAuction,
+
+        TooManyLiveBids,
+
+        ProsumerAlreadyRegistered,
+
+        ProsumerNotRegistered,
+
+        InvalidBidStep,
+
+        AutoBidTooLow,
+
+        AutoBidDoesNotExist,
+
+        TooManyAutoBids,
+
+        EmptyBatch,
+
+        BatchTooLarge,
+
+        InvalidRecurrence,
+
+        ScheduleDepositTooLow,
+
+        ScheduleDoesNotExist,
+
+        BuyerNotAllowed,
+
+        BuyerGroupDoesNotExist,
+
+        GroupNameTooLong,
+
+        TooManyGroupMembers,
+
+        EmptyBuyerList,
+
+        /// `listed_auctions` was below the number of listed auctions.
+        ListedAuctionsUnderestimated,
+    }
+
+    #[pallet::call]
+    impl<T: Config<I>, I: 'static> Pallet<T, I> {
+        /// Lists energy for sale. With `buyers` the auction is private: only the given
+        /// accounts may bid, and it is left out of the public listings.
+        #[pallet::call_index(0)]
+        #[pallet::weight(
+            Weight::from_parts(100_000_000, 0).saturating_add(T::MaxMarketHookWeight::get())
+        )]
+        pub fn new(
+            origin: OriginFor<T>,
+            energy_quantity: u128, // in KWH
+            starting_price: u128,  // in parachain native token
+            auction_period: u16,   // in minutes
+            min_bidder_reputation: Option<u32>,
+            buyers: Option<BuyerAccess<T::AccountId>>,
+        ) -> DispatchResultWithPostInfo {
+            let seller = ensure_signed(origin)?;
+
+            let listing = ListingSpec {
+                energy_quantity,
+                starting_price,
+                auction_period,
+                min_bidder_reputation,
+                buyers,
+            };
+            let (_, hook_weight) = Self::create_listing(&seller, listing)?;
+
+            Ok(Some(Weight::from_parts(100_000_000, 0).saturating_add(hook_weight)).into())
+        }
+
+        #[pallet::call_index(1)]
+        #[pallet::weight(
+            Weight::from_parts(100_000_000, 0).saturating_add(T::MaxMarketHookWeight::get())
+        )]
+        pub fn cancel(
+            origin: OriginFor<T>,
+            auction_id: T::AuctionId,
+        ) -> DispatchResultWithPostInfo {
+            let signer = ensure_signed(origin)?;
+
+            Self::ensure_bidding_allowed()?;
+
+            ensure!(Auctions::<T, I>::contains_key(auction_id), Error::<T, I>::AuctionDoesNotExist);
+
+            let mut auction_data = Auctions::<T, I>::get(auction_id).expect("data of auction");
+
+            ensure!(auction_data.seller_id == signer, Error::<T, I>::NotSeller);
+
+            ensure!(Self::is_open(&auction_data), Error::<T, I>::AuctionIsOver);
+
+            auction_data.auction_status = AuctionStatus::Closed;
+
+            Self::update_reputation(&signer, |reputation| {
+                reputation.cancellations = reputation.cancellations.saturating_add(1);
+                reputation.score = reputation.score.saturating_sub(CANCELLATION_PENALTY);
+            });
+
+            Self::release_bid(&auction_data.seller_id, &auction_data.highest_bid);
+            Self::release_listing_deposit(&auction_data);
+            RelistRounds::<T, I>::remove(auction_id);
+
+            Auctions::<T, I>::remove(auction_data.auction_id);
+
+            Self::remove_from_indexes(&auction_data);
+            Self::release_bid_deposits(&auction_data);
+
+            AuctionsExecutionQueue::<T, I>::remove(auction_data.end_at, auction_data.auction_id);
+
+            let hook_weight = T::OnAuctionCanceled::on_auction_canceled(
+                auction_data.auction_id,
+                &auction_data.seller_id,
+                auction_data.quantity,
+                auction_data.starting_bid.bid,
+            );
+
+            Self::deposit_event(Event::AuctionCanceled {
+                auction_id: auction_data.auction_id,
+                seller_id: auction_data.seller_id,
+                energy_quantity: auction_data.quantity,
+                starting_price: auction_data.starting_bid.bid,
+            });
+
+            Ok(Some(Weight::from_parts(100_000_000, 0).saturating_add(hook_weight)).into())
+        }
+
+        #[pallet::call_index(2)]
+        #[pallet::weight(
+            Weight::from_parts(100_000_000, 0)
+                .saturating_add(T::WeightInfo::resolve_auto_bids(T::MaxAutoBids::get()))
+        )]
+        pub fn bid(origin: OriginFor<T>, auction_id: T::AuctionId, bid: u128) -> DispatchResult {
+            let buyer_id = ensure_signed(origin)?;
+
+            Self::place_bid(&buyer_id, auction_id, T::Price::from(bid))
+        }
+
+        #[pallet::call_index(3)]
+        #[pallet::weight(100_000_000)]
+        pub fn set_trade_fee(
+            origin: OriginFor<T>,
+            tier_level: u32,
+            fee: Option<Perbill>,
+        ) -> DispatchResult {
+            T::TreasuryOrigin::ensure_origin(origin)?;
+
+            TierTradeFee::<T, I>::set(tier_level, fee);
+
+            Self::deposit_event(Event::TradeFeeSet { tier_level, fee });
+
+            Ok(())
+        }
+
+        #[pallet::call_index(4)]
+        #[pallet::weight(100_000_000)]
+        pub fn spend_treasury(
+            origin: OriginFor<T>,
+            #[pallet::compact] amount: BalanceOf<T, I>,
+            beneficiary: T::AccountId,
+        ) -> DispatchResult {
+            T::TreasuryOrigin::ensure_origin(origin)?;
+
+            T::Currency::transfer(
+                &Self::account_id(),
+                &beneficiary,
+                amount,
+                ExistenceRequirement::KeepAlive,
+            )
+            .map_err(|_| Error::<T, I>::InsufficientTreasuryBalance)?;
+
+            Self::deposit_event(Event::TreasurySpent { beneficiary, amount });
+
+            Ok(())
+        }
+
+        #[pallet::call_index(5)]
+        #[pallet::weight(100_000_000)]
+        pub fn raise_dispute(origin: OriginFor<T>, auction_id: T::AuctionId) -> DispatchResult {
+            let buyer_id = ensure_signed(origin)?;
+
+            ensure!(
+                !Disputes::<T, I>::contains_key(auction_id),
+                Error::<T, I>::DisputeAlreadyRaised
+            );
+
+            let escrow =
+                Escrows::<T, I>::get(auction_id).ok_or(Error::<T, I>::EscrowDoesNotExist)?;
+
+            ensure!(escrow.buyer_id == buyer_id, Error::<T, I>::NotBuyer);
+
+            let now = <frame_system::Pallet<T>>::block_number();
+            ensure!(now <= escrow.release_at, Error::<T, I>::DisputeWindowClosed);
+
+            // The payment stays reserved on the seller until the arbitrator decides.
+            EscrowReleaseQueue::<T, I>::remove(escrow.release_at, auction_id);
+
+            Disputes::<T, I>::insert(
+                auction_id,
+                Dispute {
+                    seller_id: escrow.seller_id.clone(),
+                    buyer_id: escrow.buyer_id.clone(),
+                    quantity: escrow.quantity,
+                    amount: escrow.amount,
+                    executed_at: escrow.executed_at,
+                    raised_at: now,
+                    resolved_at: None,
+                    buyer_share: None,
+                },
+            );
+
+            Self::deposit_event(Event::DisputeRaised {
+                auction_id,
+                buyer_id: escrow.buyer_id,
+                seller_id: escrow.seller_id,
+                amount: escrow.amount,
+            });
+
+            Ok(())
+        }
+
+        #[pallet::call_index(6)]
+        #[pallet::weight(100_000_000)]
+        pub fn resolve_dispute(
+            origin: OriginFor<T>,
+            auction_id: T::AuctionId,
+            buyer_share: Perbill,
+        ) -> DispatchResult {
+            T::ArbitratorOrigin::ensure_origin(origin)?;
+
+            let mut dispute =
+                Disputes::<T, I>::get(auction_id).ok_or(Error::<T, I>::DisputeDoesNotExist)?;
+
+            ensure!(dispute.resolved_at.is_none(), Error::<T, I>::DisputeAlreadyResolved);
+
+            Escrows::<T, I>::remove(auction_id);
+
+            let to_buyer = buyer_share.mul_floor(dispute.amount);
+            let to_seller = dispute.amount.saturating_sub(to_buyer);
+
+            // The split only reports what the seller's reserve could actually cover.
+            let unpaid = T::Currency::repatriate_reserved(
+                &dispute.seller_id,
+                &dispute.buyer_id,
+                to_buyer,
+                BalanceStatus::Free,
+            )?;
+            let to_buyer = to_buyer.saturating_sub(unpaid);
+            let unreleased = T::Currency::unreserve(&dispute.seller_id, to_seller);
+            let to_seller = to_seller.saturating_sub(unreleased);
+
+            let undelivered = buyer_share.mul_floor(dispute.quantity);
+            let seller_lost = buyer_share >= Perbill::from_percent(50);
+            Self::update_reputation(&dispute.seller_id, |reputation| {
+                reputation.delivered_quantity =
+                    reputation.delivered_quantity.saturating_sub(undelivered);
+                if seller_lost {
+                    reputation.lost_disputes = reputation.lost_disputes.saturating_add(1);
+                    reputation.score = reputation.score.saturating_sub(DISPUTE_PENALTY);
+                }
+            });
+            if !seller_lost {
+                Self::update_reputation(&dispute.buyer_id, |reputation| {
+                    reputation.lost_disputes = reputation.lost_disputes.saturating_add(1);
+                    reputation.score = reputation.score.saturating_sub(DISPUTE_PENALTY);
+                });
+            }
+
+            dispute.resolved_at = Some(<frame_system::Pallet<T>>::block_number());
+            dispute.buyer_share = Some(buyer_share);
+            Disputes::<T, I>::insert(auction_id, dispute.clone());
+
+            Self::deposit_event(Event::DisputeResolved {
+                auction_id,
+                buyer_id: dispute.buyer_id,
+                seller_id: dispute.seller_id,
+                to_buyer,
+                to_seller,
+            });
+
+            Ok(())
+        }
+
+        #[pallet::call_index(7)]
+        #[pallet::weight(100_000_000)]
+        pub fn set_min_seller_reputation(
+            origin: OriginFor<T>,
+            min_reputation: Option<u32>,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            MinSellerReputation::<T, I>::set(&who, min_reputation);
+
+            Self::deposit_event(Event::MinSellerReputationSet { who, min_reputation });
+
+            Ok(())
+        }
+
+        #[pallet::call_index(8)]
+        #[pallet::weight(
+            Weight::from_parts(100_000_000, 0)
+                .saturating_add(T::WeightInfo::resolve_auto_bids(T::MaxAutoBids::get()))
+        )]
+        pub fn retract_bid(origin: OriginFor<T>, auction_id: T::AuctionId) -> DispatchResult {
+            let bidder = ensure_signed(origin)?;
+
+            Self::ensure_bidding_allowed()?;
+
+            let mut auction_data =
+                Auctions::<T, I>::get(auction_id).ok_or(Error::<T, I>::AuctionDoesNotExist)?;
+
+            ensure!(Self::is_open(&auction_data), Error::<T, I>::AuctionIsOver);
+
+            ensure!(
+                bidder != auction_data.seller_id &&
+                    auction_data.bids.iter().any(|bid| bid.bidder == bidder),
+                Error::<T, I>::BidDoesNotExist
+            );
+
+            let was_highest = auction_data.highest_bid.bidder == bidder;
+            let mut penalty = Zero::zero();
+
+            if was_highest {
+                let value = Self::price_to_balance(auction_data.highest_bid.bid);
+                let charged = T::RetractionPenalty::get().mul_floor(value);
+
+                let unpaid = T::Currency::repatriate_reserved(
+                    &bidder,
+                    &Self::account_id(),
+                    charged,
+                    BalanceStatus::Free,
+                )?;
+                T::Currency::unreserve(&bidder, value.saturating_sub(charged));
+                penalty = charged.saturating_sub(unpaid);
+            }
+
+            let retracted = auction_data.bids.iter().filter(|bid| bid.bidder == bidder).count();
+            auction_data.bids.retain(|bid| bid.bidder != bidder);
+            BuyerAuctions::<T, I>::remove(&bidder, auction_id);
+            AutoBids::<T, I>::remove(auction_id, &bidder);
+            Self::drop_live_bids(&bidder, retracted as u32);
+            Self::refund_bid_deposit(auction_id, &bidder);
+
+            if was_highest {
+                // Fall back to the best remaining bid that can still be funded. The seller's
+                // starting bid is never retracted and ends the search.
+                while auction_data.bids[0].bidder != auction_data.seller_id {
+                    let candidate = &auction_data.bids[0];
+                    let value = Self::price_to_balance(candidate.bid);
+                    if T::Currency::reserve(&candidate.bidder, value).is_ok() {
+                        break;
+                    }
+                    let dropped = auction_data.bids.remove(0);
+                    Self::drop_live_bids(&dropped.bidder, 1);
+                    if !auction_data.bids.iter().any(|bid| bid.bidder == dropped.bidder) {
+                        BuyerAuctions::<T, I>::remove(&dropped.bidder, auction_id);
+                        Self::refund_bid_deposit(auction_id, &dropped.bidder);
+                    }
+                }
+                auction_data.highest_bid = auction_data.bids[0].clone();
+            }
+
+            Auctions::<T, I>::insert(auction_id, auction_data.clone());
+
+            Self::deposit_event(Event::BidRetracted {
+                auction_id,
+                bidder,
+                penalty,
+                highest_bid: auction_data.highest_bid,
+            });
+
+            // Auto bids outbid by the retracted bid may now lead again.
+            if was_highest {
+                Self::resolve_auto_bids(auction_id);
+            }
+
+            Ok(())
+        }
+
+        #[pallet::call_index(9)]
+        #[pallet::weight(100_000_000)]
+        pub fn amend(
+            origin: OriginFor<T>,
+            auction_id: T::AuctionId,
+            energy_quantity: Option<u128>, // in KWH
+            starting_price: Option<u128>,  // in parachain native token
+            auction_period: Option<u16>,   // in minutes, counted from the start of the auction
+        ) -> DispatchResult {
+            let seller = ensure_signed(origin)?;
+
+            Self::ensure_listing_allowed()?;
+
+            let mut auction_data =
+                Auctions::<T, I>::get(auction_id).ok_or(Error::<T, I>::AuctionDoesNotExist)?;
+
+            ensure!(auction_data.seller_id == seller, Error::<T, I>::NotSeller);
+
+            ensure!(Self::is_open(&auction_data), Error::<T, I>::AuctionIsOver);
+
+            ensure!(auction_data.bids.len() == 1, Error::<T, I>::AuctionHasBids);
+
+            if let Some(energy_quantity) = energy_quantity {
+                auction_data.quantity = T::Quantity::from(energy_quantity);
+                auction_data.auction_category = Self::tier_of(energy_quantity);
+            }
+
+            if let Some(starting_price) = starting_price {
+                let starting_bid = Bid::<T::AccountId, T::Price> {
+                    bidder: seller.clone(),
+                    bid: T::Price::from(starting_price),
+                };
+                auction_data.starting_bid = starting_bid.clone();
+                auction_data.bids = vec![starting_bid.clone()];
+                auction_data.highest_bid = starting_bid;
+            }
+
+            if let Some(auction_period) = auction_period {
+                ensure!(
+                    auction_period <= T::MaxAuctionPeriod::get(),
+                    Error::<T, I>::InvalidAuctionPeriod
+                );
+
+                let auction_period_in_block_number = Self::period_to_blocks(auction_period);
+                let end_at = auction_data.start_at + auction_period_in_block_number;
+
+                ensure!(
+                    end_at > <frame_system::Pallet<T>>::block_number(),
+                    Error::<T, I>::InvalidAuctionPeriod
+                );
+
+                AuctionsExecutionQueue::<T, I>::remove(auction_data.end_at, auction_id);
+                AuctionsExecutionQueue::<T, I>::insert(end_at, auction_id, ());
+
+                auction_data.auction_period = auction_period_in_block_number;
+                auction_data.end_at = end_at;
+            }
+
+            Self::ensure_starting_price_within_limits(&auction_data)?;
+
+            Auctions::<T, I>::insert(auction_id, auction_data.clone());
+
+            Self::deposit_event(Event::AuctionAmended {
+                auction_id,
+                seller_id: seller,
+                energy_quantity: auction_data.quantity,
+                starting_price: auction_data.starting_bid.bid,
+                end_at: auction_data.end_at,
+            });
+
+            Ok(())
+        }
+
+        #[pallet::call_index(10)]
+        #[pallet::weight(100_000_000)]
+        pub fn set_auto_relist(
+            origin: OriginFor<T>,
+            auction_id: T::AuctionId,
+            rounds: u32,
+        ) -> DispatchResult {
+            let seller = ensure_signed(origin)?;
+
+            let auction_data =
+                Auctions::<T, I>::get(auction_id).ok_or(Error::<T, I>::AuctionDoesNotExist)?;
+
+            ensure!(auction_data.seller_id == seller, Error::<T, I>::NotSeller);
+            ensure!(rounds <= T::MaxRelistRounds::get(), Error::<T, I>::TooManyRelistRounds);
+
+            if rounds == 0 {
+                RelistRounds::<T, I>::remove(auction_id);
+            } else {
+                RelistRounds::<T, I>::insert(auction_id, rounds);
+            }
+
+            Self::deposit_event(Event::AutoRelistSet { auction_id, rounds });
+
+            Ok(())
+        }
+
+        /// Halts or resumes trading. Auctions still running when trading was halted are
+        /// extended by the time the market stayed halted.
+        ///
+        /// `listed_auctions` must be at least the number of listed auctions, which resuming
+        /// from a halt scans. It bounds the weight charged up front, the rest is refunded.
+        #[pallet::call_index(11)]
+        #[pallet::weight(T::WeightInfo::set_market_status(*listed_auctions))]
+        pub fn set_market_status(
+            origin: OriginFor<T>,
+            status: MarketStatus,
+            listed_auctions: u32,
+        ) -> DispatchResultWithPostInfo {
+            T::PauseOrigin::ensure_origin(origin)?;
+
+            let scanned = Self::change_market_status(status, listed_auctions)?;
+
+            Ok(Some(T::WeightInfo::set_market_status(scanned)).into())
+        }
+
+        /// Sets the floor and cap per kWh of a tier, or of one of its delivery periods. Limits
+        /// of a delivery period replace those of the tier. Passing no limits removes them.
+        #[pallet::call_index(12)]
+        #[pallet::weight(100_000_000)]
+        pub fn set_price_limits(
+            origin: OriginFor<T>,
+            tier_level: u32,
+            delivery_period: Option<u32>,
+            floor: Option<u128>,
+            cap: Option<u128>,
+        ) -> DispatchResult {
+            T::RegulatorOrigin::ensure_origin(origin)?;
+
+            if let Some(delivery_period) = delivery_period {
+                ensure!(delivery_period < DELIVERY_PERIODS, Error::<T, I>::InvalidDeliveryPeriod);
+            }
+            if let (Some(floor), Some(cap)) = (floor, cap) {
+                ensure!(floor <= cap, Error::<T, I>::InvalidPriceLimits);
+            }
+
+            let floor = floor.map(T::Price::from);
+            let cap = cap.map(T::Price::from);
+            let limit = (floor.is_some() || cap.is_some()).then(|| PriceLimit { floor, cap });
+
+            match delivery_period {
+                Some(delivery_period) =>
+                    PeriodPriceLimits::<T, I>::set(tier_level, delivery_period, limit),
+                None => TierPriceLimits::<T, I>::set(tier_level, limit),
+            }
+
+            Self::deposit_event(Event::PriceLimitsUpdated {
+                tier_level,
+                delivery_period,
+                floor,
+                cap,
+            });
+
+            Ok(())
+        }
+
+        /// Registers a small prosumer, whose market calls are then fee-less up to
+        /// `SponsoredCallsPerPeriod` per `SponsorshipPeriod`.
+        #[pallet::call_index(13)]
+        #[pallet::weight(100_000_000)]
+        pub fn register_prosumer(origin: OriginFor<T>, who: T::AccountId) -> DispatchResult {
+            T::RegulatorOrigin::ensure_origin(origin)?;
+
+            ensure!(
+                !SmallProsumers::<T, I>::contains_key(&who),
+                Error::<T, I>::ProsumerAlreadyRegistered
+            );
+
+            let period_start = <frame_system::Pallet<T>>::block_number();
+            let sponsorship = Sponsorship { period_start, ..Default::default() };
+            SmallProsumers::<T, I>::insert(&who, sponsorship);
+
+            Self::deposit_event(Event::ProsumerRegistered { who });
+
+            Ok(())
+        }
+
+        #[pallet::call_index(14)]
+        #[pallet::weight(100_000_000)]
+        pub fn deregister_prosumer(origin: OriginFor<T>, who: T::AccountId) -> DispatchResult {
+            T::RegulatorOrigin::ensure_origin(origin)?;
+
+            ensure!(
+                SmallProsumers::<T, I>::take(&who).is_some(),
+                Error::<T, I>::ProsumerNotRegistered
+            );
+
+            Self::deposit_event(Event::ProsumerDeregistered { who });
+
+            Ok(())
+        }
+
+        /// Bids on behalf of the caller whenever it is outbid, raising its bid by `step` up to
+        /// `max_price`. Between competing auto bids the one with the higher maximum wins at one
+        /// step above the other's maximum, or at its own maximum if that is lower. Setting an
+        /// auto bid again replaces it.
+        #[pallet::call_index(15)]
+        #[pallet::weight(
+            Weight::from_parts(100_000_000, 0)
+                .saturating_add(T::WeightInfo::resolve_auto_bids(T::MaxAutoBids::get()))
+        )]
+        pub fn set_auto_bid(
+            origin: OriginFor<T>,
+            auction_id: T::AuctionId,
+            max_price: u128,
+            step: u128,
+        ) -> DispatchResult {
+            let bidder = ensure_signed(origin)?;
+
+            Self::ensure_bidding_allowed()?;
+
+            let auction_data =
+                Auctions::<T, I>::get(auction_id).ok_or(Error::<T, I>::AuctionDoesNotExist)?;
+
+            ensure!(Self::is_open(&auction_data), Error::<T, I>::AuctionIsOver);
+            ensure!(auction_data.seller_id != bidder, Error::<T, I>::SellerCannotBid);
+            ensure!(Self::is_buyer_allowed(&auction_data, &bidder), Error::<T, I>::BuyerNotAllowed);
+            ensure!(step > 0, Error::<T, I>::InvalidBidStep);
+
+            let max_price = T::Price::from(max_price);
+            let highest = &auction_data.highest_bid;
+            ensure!(
+                max_price > highest.bid || (highest.bidder == bidder && max_price == highest.bid),
+                Error::<T, I>::AutoBidTooLow
+            );
+
+            if !AutoBids::<T, I>::contains_key(auction_id, &bidder) {
+                let count = AutoBids::<T, I>::iter_prefix(auction_id).count() as u32;
+                ensure!(count < T::MaxAutoBids::get(), Error::<T, I>::TooManyAutoBids);
+            }
+
+            let set_at = (
+                <frame_system::Pallet<T>>::block_number(),
+                <frame_system::Pallet<T>>::extrinsic_index().unwrap_or_default(),
+            );
+            let auto_bid = AutoBid { max_price, step: T::Price::from(step), set_at };
+            AutoBids::<T, I>::insert(auction_id, &bidder, auto_bid);
+
+            Self::deposit_event(Event::AutoBidSet { auction_id, bidder });
+
+            Self::resolve_auto_bids(auction_id);
+
+            Ok(())
+        }
+
+        #[pallet::call_index(16)]
+        #[pallet::weight(100_000_000)]
+        pub fn cancel_auto_bid(origin: OriginFor<T>, auction_id: T::AuctionId) -> DispatchResult {
+            let bidder = ensure_signed(origin)?;
+
+            ensure!(
+                AutoBids::<T, I>::take(auction_id, &bidder).is_some(),
+                Error::<T, I>::AutoBidDoesNotExist
+            );
+
+            Self::deposit_event(Event::AutoBidCanceled { auction_id, bidder });
+
+            Ok(())
+        }
+
+        /// Lists several auctions at once. Either all listings succeed or none does, failing
+        /// with the error of the first listing that could not be made.
+        #[pallet::call_index(17)]
+        #[pallet::weight(
+            T::WeightInfo::new_batch(listings.len() as u32).saturating_add(
+                T::MaxMarketHookWeight::get().saturating_mul(listings.len() as u64),
+            )
+        )]
+        pub fn new_batch(
+            origin: OriginFor<T>,
+            listings: Vec<ListingSpec<T::AccountId>>,
+        ) -> DispatchResultWithPostInfo {
+            let seller = ensure_signed(origin)?;
+
+            Self::ensure_batch_size(listings.len())?;
+
+            let mut weight = T::WeightInfo::new_batch(listings.len() as u32);
+            let mut auction_ids = Vec::with_capacity(listings.len());
+            for listing in listings {
+                let (auction_id, hook_weight) = Self::create_listing(&seller, listing)?;
+                weight.saturating_accrue(hook_weight);
+                auction_ids.push(auction_id);
+            }
+
+            Self::deposit_event(Event::ListingBatchCreated { seller_id: seller, auction_ids });
+
+            Ok(Some(weight).into())
+        }
+
+        /// Bids on several auctions at once. Either all bids succeed or none does, failing with
+        /// the error of the first bid that could not be placed.
+        #[pallet::call_index(18)]
+        #[pallet::weight(
+            T::WeightInfo::bid_batch(bids.len() as u32).saturating_add(
+                T::WeightInfo::resolve_auto_bids(T::MaxAutoBids::get())
+                    .saturating_mul(bids.len() as u64),
+            )
+        )]
+        pub fn bid_batch(origin: OriginFor<T>, bids: Vec<(T::AuctionId, u128)>) -> DispatchResult {
+            let bidder = ensure_signed(origin)?;
+
+            Self::ensure_batch_size(bids.len())?;
+
+            let mut highest_bids = Vec::with_capacity(bids.len());
+            for (auction_id, bid) in bids {
+                Self::place_bid(&bidder, auction_id, T::Price::from(bid))?;
+                if let Some(highest_bid) = Self::highest_bid(auction_id) {
+                    highest_bids.push((auction_id, highest_bid));
+                }
+            }
+
+            Self::deposit_event(Event::BidBatchPlaced { bidder, highest_bids });
+
+            Ok(())
+        }
+
+        /// Lists `listing` again and again, following `recurrence`. `deposit` is reserved and
+        /// pays the listing fee of every auction; the listing deposits are reserved as usual.
+        /// The schedule ends when it is canceled or its deposit can't pay another listing fee.
+        #[pallet::call_index(19)]
+        #[pallet::weight(100_000_000)]
+        pub fn create_schedule(
+            origin: OriginFor<T>,
+            mut listing: ListingSpec<T::AccountId>,
+            recurrence: Recurrence<BlockNumberFor<T>>,
+            deposit: BalanceOf<T, I>,
+        ) -> DispatchResult {
+            let seller = ensure_signed(origin)?;
+
+            listing.buyers =
+                listing.buyers.map(|buyers| Self::valid_access(&seller, buyers)).transpose()?;
+            ensure!(
+                listing.auction_period <= T::MaxAuctionPeriod::get(),
+                Error::<T, I>::InvalidAuctionPeriod
+            );
+            ensure!(
+                match recurrence {
+                    Recurrence::Every(blocks) => !blocks.is_zero(),
+                    Recurrence::DailyAt(minute) => minute < MINUTES_PER_DAY,
+                },
+                Error::<T, I>::InvalidRecurrence
+            );
+            ensure!(deposit >= T::ListingFee::get(), Error::<T, I>::ScheduleDepositTooLow);
+
+            T::Currency::reserve(&seller, deposit)
+                .map_err(|_| Error::<T, I>::InsuffficientAttachedDeposit)?;
+
+            let schedule_id = ScheduleIndex::<T, I>::get();
+            ScheduleIndex::<T, I>::put(schedule_id.saturating_add(1));
+
+            let now = <frame_system::Pallet<T>>::block_number();
+            let next_run = Self::next_run(&recurrence, now);
+            Schedules::<T, I>::insert(
+                schedule_id,
+                Schedule { seller_id: seller.clone(), listing, recurrence, deposit, next_run },
+            );
+            ScheduleQueue::<T, I>::insert(next_run, schedule_id, ());
+
+            Self::deposit_event(Event::ScheduleCreated {
+                schedule_id,
+                seller_id: seller,
+                next_run,
+            });
+
+            Ok(())
+        }
+
+        /// Stops a schedule and returns what is left of its deposit. Auctions it already listed
+        /// keep running.
+        #[pallet::call_index(20)]
+        #[pallet::weight(100_000_000)]
+        pub fn cancel_schedule(origin: OriginFor<T>, schedule_id: u32) -> DispatchResult {
+            let signer = ensure_signed(origin)?;
+
+            let schedule =
+                Schedules::<T, I>::get(schedule_id).ok_or(Error::<T, I>::ScheduleDoesNotExist)?;
+            ensure!(schedule.seller_id == signer, Error::<T, I>::NotSeller);
+
+            Self::end_schedule(schedule_id, &schedule);
+
+            Self::deposit_event(Event::ScheduleCanceled { schedule_id });
+
+            Ok(())
+        }
+
+        /// Sets the members of one of the caller's buyer groups. Private auctions of the caller
+        /// restricted to the group follow its current members. No members remove the group.
+        #[pallet::call_index(21)]
+        #[pallet::weight(100_000_000)]
+        pub fn set_buyer_group(
+            origin: OriginFor<T>,
+            name: Vec<u8>,
+            members: Vec<T::AccountId>,
+        ) -> DispatchResult {
+            let owner = ensure_signed(origin)?;
+
+            let mut members = members;
+            members.sort();
+            members.dedup();
+
+            ensure!(name.len() <= MAX_GROUP_NAME_LEN, Error::<T, I>::GroupNameTooLong);
+            ensure!(
+                members.len() <= T::MaxGroupMembers::get() as usize,
+                Error::<T, I>::TooManyGroupMembers
+            );
+
+            if members.is_empty() {
+                ensure!(
+                    BuyerGroups::<T, I>::take(&owner, &name).is_some(),
+                    Error::<T, I>::BuyerGroupDoesNotExist
+                );
+                Self::deposit_event(Event::BuyerGroupRemoved { owner, name });
+            } else {
+                let count = members.len() as u32;
+                BuyerGroups::<T, I>::insert(&owner, &name, members);
+                Self::deposit_event(Event::BuyerGroupUpdated { owner, name, members: count });
+            }
+
+            Ok(())
+        }
+    }
+
+    impl<T: Config<I>, I: 'static> Pallet<T, I> {
+        /// Lists an auction. Returns its id and the weight of the `OnAuctionCreated` handler,
+        /// which the caller has to account for.
+        fn do_create_auction(
+            seller: &T::AccountId,
+            quantity: T::Quantity,
+            starting_price: T::Price,
+            auction_period: u16,
+            min_bidder_reputation: Option<u32>,
+        ) -> Result<(T::AuctionId, Weight), DispatchError> {
+            Self::ensure_listing_allowed()?;
+
+            ensure!(
+                auction_period <= T::MaxAuctionPeriod::get(),
+                Error::<T, I>::InvalidAuctionPeriod
+            );
+
+            T::Currency::transfer(
+                seller,
+                &Self::account_id(),
+                T::ListingFee::get(),
+                ExistenceRequirement::KeepAlive,
+            )?;
 
             let current_auction_id = AuctionIndex::<T, I>::get().unwrap_or_default();
 
-            let auction_period_in_block_number = (auction_period.checked_mul(60).unwrap())
-                .checked_div(6)
-                .unwrap()
-                .into();
+            let deposit = T::ListingDeposit::get();
+            T::Currency::reserve(seller, deposit)
+                .map_err(|_| Error::<T, I>::InsuffficientAttachedDeposit)?;
+            ListingDeposits::<T, I>::insert(current_auction_id, deposit);
+
+            let auction_period_in_block_number = Self::period_to_blocks(auction_period);
+
+            let starting_block_number = <frame_system::Pallet<T>>::block_number();
+
+            let ending_block_number = starting_block_number + auction_period_in_block_number;
+
+            let starting_bid =
+                Bid::<T::AccountId, T::Price> { bidder: seller.clone(), bid: starting_price };
+
+            let category = Self::tier_of(quantity.saturated_into());
+
+            let auction_data = AuctionData {
+                auction_id: current_auction_id,
+                seller_id: seller.clone(),
+                quantity,
+                starting_bid: starting_bid.clone(),
+                bids: vec![starting_bid.clone()],
+                auction_period: auction_period_in_block_number,
+                auction_status: AuctionStatus::default(),
+                start_at: starting_block_number,
+                end_at: ending_block_number,
+                highest_bid: starting_bid,
+                auction_category: category,
+                min_bidder_reputation,
+                delivery_period: Self::delivery_period(ending_block_number),
+            };
+
+            Self::ensure_starting_price_within_limits(&auction_data)?;
+
+            SellerAuctions::<T, I>::insert(seller, auction_data.auction_id, ());
+            Self::append_history(seller, auction_data.auction_id, PartyType::Seller);
+
+            AuctionsExecutionQueue::<T, I>::insert(
+                auction_data.end_at,
+                auction_data.auction_id,
+                (),
+            );
+
+            Auctions::<T, I>::insert(&auction_data.auction_id, auction_data.clone());
+
+            let next_id = current_auction_id + T::AuctionId::from(1u64);
+            AuctionIndex::<T, I>::set(Some(next_id));
+
+            let hook_weight = T::OnAuctionCreated::on_auction_created(
+                auction_data.auction_id,
+                seller,
+                auction_data.quantity,
+                auction_data.starting_bid.bid,
+            );
+
+            Self::deposit_event(Event::AuctionCreated {
+                auction_id: auction_data.auction_id,
+                seller_id: seller.clone(),
+                energy_quantity: auction_data.quantity,
+                starting_price: auction_data.starting_bid.bid,
+                seller_reputation: Self::reputation_score(&auction_data.seller_id),
+            });
+
+            Ok((auction_data.auction_id, hook_weight))
+        }
+
+        /// Places `bid` on an auction. An automatic raise replaces the bidder's latest entry, so
+        /// it counts towards neither `MaxBidsPerAuction` nor `MaxLiveBids` and holds no further
+        /// bid deposit.
+        fn do_place_bid(
+            buyer_id: &T::AccountId,
+            auction_id: T::AuctionId,
+            bid: T::Price,
+            automatic: bool,
+        ) -> DispatchResult {
+            Self::ensure_bidding_allowed()?;
+
+            ensure!(Auctions::<T, I>::contains_key(auction_id), Error::<T, I>::AuctionDoesNotExist);
+
+            let mut auction_data = Auctions::<T, I>::get(auction_id).expect("data of auction");
+
+            ensure!(Self::is_open(&auction_data), Error::<T, I>::AuctionIsOver);
+
+            ensure!(&auction_data.seller_id != buyer_id, Error::<T, I>::SellerCannotBid);
+
+            ensure!(
+                Self::is_buyer_allowed(&auction_data, buyer_id),
+                Error::<T, I>::BuyerNotAllowed
+            );
+
+            if let Some(min_reputation) = auction_data.min_bidder_reputation {
+                ensure!(
+                    Self::reputation_score(buyer_id) >= min_reputation,
+                    Error::<T, I>::BidderReputationTooLow
+                );
+            }
+
+            if let Some(min_reputation) = MinSellerReputation::<T, I>::get(buyer_id) {
+                ensure!(
+                    Self::reputation_score(&auction_data.seller_id) >= min_reputation,
+                    Error::<T, I>::SellerReputationTooLow
+                );
+            }
+
+            let new_bid = Bid::<T::AccountId, T::Price> { bidder: buyer_id.clone(), bid };
+
+            let limit = Self::lot_price_limit(&auction_data);
+            ensure!(
+                limit.floor.map_or(true, |floor| new_bid.bid >= floor),
+                Error::<T, I>::BidBelowFloor
+            );
+            ensure!(limit.cap.map_or(true, |cap| new_bid.bid <= cap), Error::<T, I>::BidAboveCap);
+
+            ensure!(new_bid.bid > auction_data.highest_bid.bid, Error::<T, I>::BidTooLow);
+
+            let replaced = automatic
+                .then(|| auction_data.bids.iter().position(|bid| &bid.bidder == buyer_id))
+                .flatten();
+
+            if replaced.is_none() {
+                let own_bids =
+                    auction_data.bids.iter().filter(|bid| &bid.bidder == buyer_id).count();
+                ensure!(
+                    (own_bids as u32) < T::MaxBidsPerAuction::get(),
+                    Error::<T, I>::TooManyBidsInAuction
+                );
+                ensure!(
+                    LiveBids::<T, I>::get(buyer_id) < T::MaxLiveBids::get(),
+                    Error::<T, I>::TooManyLiveBids
+                );
+
+                let deposit = T::BidDeposit::get();
+                T::Currency::reserve(buyer_id, deposit)
+                    .map_err(|_| Error::<T, I>::InsuffficientAttachedDeposit)?;
+                BidDeposits::<T, I>::mutate(auction_id, buyer_id, |held| {
+                    *held = Some(held.unwrap_or_default().saturating_add(deposit))
+                });
+                LiveBids::<T, I>::mutate(buyer_id, |live| *live = live.saturating_add(1));
+            }
+
+            T::Currency::reserve(buyer_id, Self::price_to_balance(new_bid.bid))
+                .map_err(|_| Error::<T, I>::InsuffficientAttachedDeposit)?;
+            Self::release_bid(&auction_data.seller_id, &auction_data.highest_bid);
+
+            if !BuyerAuctions::<T, I>::contains_key(buyer_id, auction_id) {
+                BuyerAuctions::<T, I>::insert(buyer_id, auction_id, ());
+                Self::append_history(buyer_id, auction_id, PartyType::Buyer);
+            }
+
+            if let Some(index) = replaced {
+                auction_data.bids.remove(index);
+            }
+            auction_data.bids.insert(0, new_bid.clone());
+            auction_data.highest_bid = new_bid.clone();
+
+            Auctions::<T, I>::insert(&auction_data.auction_id, auction_data.clone());
+
+            Self::deposit_event(Event::AuctionBidAdded {
+                auction_id: auction_data.auction_id,
+                seller_id: auction_data.seller_id,
+                energy_quantity: auction_data.quantity,
+                bid: new_bid,
+            });
+
+            Ok(())
+        }
+
+        /// Handles the auctions, escrows and settlements due at `block` while `budget` lasts.
+        /// Anything left stays queued and is picked up again by the next call for `block`.
+        fn process_due(block: BlockNumberFor<T>, budget: &mut u32) -> Weight {
+            let mut weight = Weight::zero();
+
+            let ended: Vec<T::AuctionId> = AuctionsExecutionQueue::<T, I>::iter_key_prefix(block)
+                .take(*budget as usize)
+                .collect();
+            for auction_id in ended {
+                // A trade may trip the circuit breaker, the rest waits for trading to resume.
+                if Self::is_halted() {
+                    return weight;
+                }
+                AuctionsExecutionQueue::<T, I>::remove(block, auction_id);
+                if Auctions::<T, I>::contains_key(auction_id) {
+                    weight.saturating_accrue(Self::on_auction_ended(auction_id));
+                } else {
+                    weight.saturating_accrue(T::WeightInfo::expire_auction(0, 0));
+                }
+                *budget -= 1;
+            }
+
+            let released: Vec<T::AuctionId> = EscrowReleaseQueue::<T, I>::iter_key_prefix(block)
+                .take(*budget as usize)
+                .collect();
+            for auction_id in released {
+                EscrowReleaseQueue::<T, I>::remove(block, auction_id);
+                Self::release_escrow(auction_id);
+                weight.saturating_accrue(T::WeightInfo::release_escrow());
+                *budget -= 1;
+            }
+
+            let pruned: Vec<T::AuctionId> = SettlementPruningQueue::<T, I>::iter_key_prefix(block)
+                .take(*budget as usize)
+                .collect();
+            for auction_id in pruned {
+                SettlementPruningQueue::<T, I>::remove(block, auction_id);
+                Settlements::<T, I>::remove(auction_id);
+                weight.saturating_accrue(T::WeightInfo::prune_settlement());
+                *budget -= 1;
+            }
+
+            let runs: Vec<u32> =
+                ScheduleQueue::<T, I>::iter_key_prefix(block).take(*budget as usize).collect();
+            for schedule_id in runs {
+                ScheduleQueue::<T, I>::remove(block, schedule_id);
+                let hook_weight = Self::run_schedule(schedule_id);
+                weight.saturating_accrue(T::WeightInfo::run_schedule());
+                weight.saturating_accrue(hook_weight);
+                *budget -= 1;
+            }
+
+            weight
+        }
+
+        /// Lists the next auction of a schedule and queues its following run, or ends the
+        /// schedule once its deposit is used up. Returns the weight of the `OnAuctionCreated`
+        /// handler.
+        fn run_schedule(schedule_id: u32) -> Weight {
+            let Some(mut schedule) = Schedules::<T, I>::get(schedule_id) else {
+                return Weight::zero()
+            };
+            let fee = T::ListingFee::get();
+            let mut hook_weight = Weight::zero();
+
+            if schedule.deposit >= fee {
+                let seller = &schedule.seller_id;
+                let listing = &schedule.listing;
+                let created = with_storage_layer(|| {
+                    T::Currency::unreserve(seller, fee);
+                    // A buyer group may have been removed since the schedule was created.
+                    let buyers = listing
+                        .buyers
+                        .clone()
+                        .map(|buyers| Self::valid_access(seller, buyers))
+                        .transpose()?;
+                    let created = Self::do_create_auction(
+                        seller,
+                        T::Quantity::from(listing.energy_quantity),
+                        T::Price::from(listing.starting_price),
+                        listing.auction_period,
+                        listing.min_bidder_reputation,
+                    )?;
+                    if let Some(buyers) = buyers {
+                        AuctionAccess::<T, I>::insert(created.0, buyers);
+                    }
+                    Ok::<_, DispatchError>(created)
+                });
+                match created {
+                    Ok((auction_id, weight)) => {
+                        hook_weight = weight;
+                        schedule.deposit = schedule.deposit.saturating_sub(fee);
+                        Self::deposit_event(Event::ScheduleRun { schedule_id, auction_id });
+                    },
+                    Err(error) =>
+                        Self::deposit_event(Event::ScheduleRunFailed { schedule_id, error }),
+                }
+            }
+
+            if schedule.deposit < fee {
+                Self::end_schedule(schedule_id, &schedule);
+                Self::deposit_event(Event::ScheduleExhausted { schedule_id });
+            } else {
+                let now = <frame_system::Pallet<T>>::block_number();
+                schedule.next_run = Self::next_run(&schedule.recurrence, now);
+                ScheduleQueue::<T, I>::insert(schedule.next_run, schedule_id, ());
+                Schedules::<T, I>::insert(schedule_id, schedule);
+            }
+
+            hook_weight
+        }
+
+        fn end_schedule(
+            schedule_id: u32,
+            schedule: &Schedule<T::AccountId, BalanceOf<T, I>, BlockNumberFor<T>>,
+        ) {
+            Schedules::<T, I>::remove(schedule_id);
+            ScheduleQueue::<T, I>::remove(schedule.next_run, schedule_id);
+            T::Currency::unreserve(&schedule.seller_id, schedule.deposit);
+        }
+
+        /// The first block after `after` a schedule with `recurrence` runs at.
+        fn next_run(
+            recurrence: &Recurrence<BlockNumberFor<T>>,
+            after: BlockNumberFor<T>,
+        ) -> BlockNumberFor<T> {
+            match recurrence {
+                Recurrence::Every(blocks) => after.saturating_add(*blocks),
+                Recurrence::DailyAt(minute) => {
+                    let day = Self::period_to_blocks(MINUTES_PER_DAY);
+                    let at = after - after % day + Self::period_to_blocks(*minute);
+                    if at > after {
+                        at
+                    } else {
+                        at.saturating_add(day)
+                    }
+                },
+            }
+        }
+
+        /// Whether an auction still accepts bids and changes. Auctions past their end are
+        /// frozen until the execution queue reaches them.
+        fn is_open(auction_data: &AuctionDataOf<T, I>) -> bool {
+            matches!(auction_data.auction_status, AuctionStatus::Open) &&
+                <frame_system::Pallet<T>>::block_number() <= auction_data.end_at
+        }
+
+        /// Checks the buyers a seller restricts an auction to, returning them with duplicates
+        /// removed from an allow-list.
+        fn valid_access(
+            seller: &T::AccountId,
+            buyers: BuyerAccess<T::AccountId>,
+        ) -> Result<BuyerAccess<T::AccountId>, DispatchError> {
+            match buyers {
+                BuyerAccess::AllowList(mut accounts) => {
+                    accounts.sort();
+                    accounts.dedup();
+                    ensure!(!accounts.is_empty(), Error::<T, I>::EmptyBuyerList);
+                    ensure!(
+                        accounts.len() <= T::MaxGroupMembers::get() as usize,
+                        Error::<T, I>::TooManyGroupMembers
+                    );
+                    Ok(BuyerAccess::AllowList(accounts))
+                },
+                BuyerAccess::Group(name) => {
+                    ensure!(
+                        BuyerGroups::<T, I>::contains_key(seller, &name),
+                        Error::<T, I>::BuyerGroupDoesNotExist
+                    );
+                    Ok(BuyerAccess::Group(name))
+                },
+            }
+        }
 
-            let starting_block_number = <frame_system::Pallet<T>>::block_number();
+        /// Lists `listing` for `seller`, charging the listing fee, and restricts it to its
+        /// buyers if it has any. Returns the auction id and the weight of the
+        /// `OnAuctionCreated` handler.
+        fn create_listing(
+            seller: &T::AccountId,
+            listing: ListingSpec<T::AccountId>,
+        ) -> Result<(T::AuctionId, Weight), DispatchError> {
+            let buyers =
+                listing.buyers.map(|buyers| Self::valid_access(seller, buyers)).transpose()?;
+
+            let (auction_id, hook_weight) = Self::do_create_auction(
+                seller,
+                T::Quantity::from(listing.energy_quantity),
+                T::Price::from(listing.starting_price),
+                listing.auction_period,
+                listing.min_bidder_reputation,
+            )?;
+
+            if let Some(buyers) = buyers {
+                AuctionAccess::<T, I>::insert(auction_id, buyers);
+            }
 
-            let ending_block_number = starting_block_number + auction_period_in_block_number;
+            Ok((auction_id, hook_weight))
+        }
 
-            let starting_bid = Bid::<T::AccountId, T::Price> {
-                bidder: seller.clone(),
-                bid: T::Price::from(starting_price),
-            };
+        /// Whether `who` may bid on the auction. Anyone may bid on public auctions.
+        fn is_buyer_allowed(auction_data: &AuctionDataOf<T, I>, who: &T::AccountId) -> bool {
+            match AuctionAccess::<T, I>::get(auction_data.auction_id) {
+                None => true,
+                Some(BuyerAccess::AllowList(accounts)) => accounts.contains(who),
+                Some(BuyerAccess::Group(name)) =>
+                    BuyerGroups::<T, I>::get(&auction_data.seller_id, name)
+                        .map_or(false, |members| members.contains(who)),
+            }
+        }
 
-            let category;
-            if energy_quantity < 5 {
-                category = Tier::default()
-            } else {
-                category = Tier { level: 2 }
+        fn ensure_batch_size(len: usize) -> DispatchResult {
+            ensure!(len > 0, Error::<T, I>::EmptyBatch);
+            ensure!(len <= T::MaxBatchSize::get() as usize, Error::<T, I>::BatchTooLarge);
+            Ok(())
+        }
+
+        fn is_halted() -> bool {
+            MarketState::<T, I>::get() == MarketStatus::Halted
+        }
+
+        fn ensure_listing_allowed() -> DispatchResult {
+            match MarketState::<T, I>::get() {
+                MarketStatus::Open => Ok(()),
+                MarketStatus::BidsOnly => Err(Error::<T, I>::MarketNotOpen.into()),
+                MarketStatus::Halted => Err(Error::<T, I>::MarketHalted.into()),
             }
+        }
 
-            let auction_data = AuctionData {
-                auction_id: current_auction_id,
-                seller_id: seller.clone(),
-                quantity: T::Quantity::from(energy_quantity),
-                starting_bid: starting_bid.clone(),
-                bids: vec![starting_bid.clone()],
-                auction_period: auction_period_in_block_number,
-                auction_status: AuctionStatus::default(),
-                start_at: starting_block_number,
-                end_at: ending_block_number,
-                highest_bid: starting_bid,
-                auction_category: category,
-            };
+        fn ensure_bidding_allowed() -> DispatchResult {
+            ensure!(!Self::is_halted(), Error::<T, I>::MarketHalted);
+            Ok(())
+        }
 
-            let mut seller_auction_info =
-                AuctionsOf::<T, I>::get(seller.clone()).unwrap_or(AuctionInfo {
-                    participant_id: None,
-                    party_type: PartyType::Seller,
-                    auctions: vec![],
+        /// Lets the auto bids of an auction answer its highest bid. The auto bid with the
+        /// highest maximum, the earliest on a tie, bids one step above the best competing
+        /// maximum or standing bid, capped at its own maximum. Auto bids that can no longer win
+        /// or fail to bid are removed.
+        fn resolve_auto_bids(auction_id: T::AuctionId) {
+            loop {
+                let Some(auction_data) = Auctions::<T, I>::get(auction_id) else { return };
+                let highest = auction_data.highest_bid;
+
+                let mut auto_bids: Vec<_> = AutoBids::<T, I>::iter_prefix(auction_id).collect();
+                auto_bids.sort_by(|(_, a), (_, b)| {
+                    b.max_price.cmp(&a.max_price).then(a.set_at.cmp(&b.set_at))
                 });
+                let Some((leader, auto_bid)) = auto_bids.first().cloned() else { return };
+
+                let competing = auto_bids.iter().skip(1).map(|(_, other)| other.max_price).max();
+                let needs_bid = if leader == highest.bidder {
+                    competing.map_or(false, |max| max > highest.bid)
+                } else {
+                    auto_bid.max_price > highest.bid
+                };
+
+                if needs_bid {
+                    let to_beat = competing.map_or(highest.bid, |max| max.max(highest.bid));
+                    let target = to_beat.saturating_add(auto_bid.step).min(auto_bid.max_price);
+                    let placed = with_storage_layer(|| {
+                        Self::do_place_bid(&leader, auction_id, target, true)
+                    });
+                    if let Err(error) = placed {
+                        AutoBids::<T, I>::remove(auction_id, &leader);
+                        Self::deposit_event(Event::AutoBidFailed {
+                            auction_id,
+                            bidder: leader,
+                            error,
+                        });
+                        continue;
+                    }
+                }
 
-            if seller_auction_info.auctions.len() > 5 {
-                seller_auction_info.auctions.pop();
+                let Some(highest) = Self::highest_bid(auction_id) else { return };
+                for (bidder, other) in auto_bids {
+                    if bidder != highest.bidder && other.max_price <= highest.bid {
+                        AutoBids::<T, I>::remove(auction_id, &bidder);
+                        Self::deposit_event(Event::AutoBidExhausted { auction_id, bidder });
+                    }
+                }
+                return;
             }
+        }
 
-            seller_auction_info.auctions.push(auction_data.clone());
+        /// The quota slot the next sponsored call of `who` takes, as the start of its period and
+        /// the calls already made in it. Returns `None`, and market calls are charged, unless
+        /// `who` is a registered small prosumer with quota left.
+        pub(crate) fn sponsored_slot(who: &T::AccountId) -> Option<(BlockNumberFor<T>, u32)> {
+            let sponsorship = SmallProsumers::<T, I>::get(who)?;
+            if Self::period_expired(&sponsorship) {
+                return Some((<frame_system::Pallet<T>>::block_number(), 0));
+            }
+            (sponsorship.used < T::SponsoredCallsPerPeriod::get())
+                .then_some((sponsorship.period_start, sponsorship.used))
+        }
 
-            seller_auction_info = AuctionInfo {
-                participant_id: Some(seller.clone()),
-                party_type: PartyType::Seller,
-                auctions: seller_auction_info.auctions,
-            };
-            AuctionsOf::<T, I>::insert(&seller, seller_auction_info);
+        /// Counts a sponsored call against the quota of `who`.
+        pub(crate) fn use_sponsorship(who: &T::AccountId) {
+            let now = <frame_system::Pallet<T>>::block_number();
+            let used = SmallProsumers::<T, I>::mutate(who, |sponsorship| {
+                let sponsorship = sponsorship.as_mut()?;
+                if Self::period_expired(sponsorship) {
+                    sponsorship.period_start = now;
+                    sponsorship.used = 0;
+                }
+                sponsorship.used = sponsorship.used.saturating_add(1);
+                Some(sponsorship.used)
+            });
 
-            AuctionsExecutionQueue::<T, I>::insert(
-                auction_data.end_at,
-                auction_data.auction_id,
-                (),
-            );
+            if let Some(used) = used {
+                Self::deposit_event(Event::FeeSponsored { who: who.clone(), used });
+            }
+        }
 
-            Auctions::<T, I>::insert(&auction_data.auction_id, auction_data.clone());
+        /// Records a failed sponsored call, revoking the sponsorship once `who` reaches
+        /// `MaxSponsoredFailures`.
+        pub(crate) fn record_sponsored_failure(who: &T::AccountId) {
+            let Some(mut sponsorship) = SmallProsumers::<T, I>::get(who) else { return };
+            sponsorship.failures = sponsorship.failures.saturating_add(1);
+
+            if sponsorship.failures >= T::MaxSponsoredFailures::get() {
+                SmallProsumers::<T, I>::remove(who);
+                Self::deposit_event(Event::SponsorshipRevoked {
+                    who: who.clone(),
+                    failures: sponsorship.failures,
+                });
+            } else {
+                SmallProsumers::<T, I>::insert(who, sponsorship);
+            }
+        }
 
-            let next_id = current_auction_id + T::AuctionId::from(1u64);
-            AuctionIndex::<T, I>::set(Some(next_id));
+        fn period_expired(sponsorship: &Sponsorship<BlockNumberFor<T>>) -> bool {
+            let now = <frame_system::Pallet<T>>::block_number();
+            now.saturating_sub(sponsorship.period_start) >= T::SponsorshipPeriod::get()
+        }
 
-            Self::deposit_event(Event::AuctionCreated {
-                auction_id: auction_data.auction_id,
-                seller_id: seller,
-                energy_quantity: auction_data.quantity,
-                starting_price: auction_data.starting_bid.bid,
-            });
+        /// Switches the market to `status`. Resuming from a halt postpones the auctions still
+        /// running, scanning at most `listed_auctions` auctions. Returns the number scanned.
+        fn change_market_status(
+            status: MarketStatus,
+            listed_auctions: u32,
+        ) -> Result<u32, DispatchError> {
+            let now = <frame_system::Pallet<T>>::block_number();
+            let previous = MarketState::<T, I>::get();
+            if previous == status {
+                return Ok(0);
+            }
+            if status == MarketStatus::Halted {
+                Self::halt_market();
+                return Ok(0);
+            }
 
-            Ok(())
+            let mut scanned = 0;
+            if let Some(halted_since) = HaltedSince::<T, I>::take() {
+                let delay = now.saturating_sub(halted_since);
+                scanned = Self::postpone_auctions(halted_since, delay, listed_auctions)?;
+            }
+
+            MarketState::<T, I>::put(status);
+
+            Self::deposit_event(Event::MarketStatusChanged { status });
+
+            Ok(scanned)
         }
 
-        #[pallet::call_index(1)]
-        #[pallet::weight(100_000_000)]
-        pub fn cancel(origin: OriginFor<T>, auction_id: T::AuctionId) -> DispatchResult {
+        fn halt_market() {
+            if MarketState::<T, I>::get() == MarketStatus::Halted {
+                return;
+            }
 
-            let _signer = ensure_signed(origin)?;
+            HaltedSince::<T, I>::put(<frame_system::Pallet<T>>::block_number());
+            MarketState::<T, I>::put(MarketStatus::Halted);
 
-            ensure!(
-                Auctions::<T, I>::contains_key(auction_id),
-                Error::<T, I>::AuctionDoesNotExist
+            Self::deposit_event(Event::MarketStatusChanged { status: MarketStatus::Halted });
+        }
+
+        /// Moves the end of every auction still running at `halted_since` back by `delay`.
+        /// Auctions that had already ended stay queued where they are. Fails if there are
+        /// more than `max_scanned` listed auctions, returns the number scanned otherwise.
+        fn postpone_auctions(
+            halted_since: BlockNumberFor<T>,
+            delay: BlockNumberFor<T>,
+            max_scanned: u32,
+        ) -> Result<u32, DispatchError> {
+            if delay.is_zero() {
+                return Ok(0);
+            }
+
+            let mut scanned = 0u32;
+            let mut running: Vec<AuctionDataOf<T, I>> = Vec::new();
+            for auction in Auctions::<T, I>::iter_values() {
+                ensure!(scanned < max_scanned, Error::<T, I>::ListedAuctionsUnderestimated);
+                scanned += 1;
+                if auction.end_at >= halted_since {
+                    running.push(auction);
+                }
+            }
+            for mut auction in running {
+                AuctionsExecutionQueue::<T, I>::remove(auction.end_at, auction.auction_id);
+                auction.end_at = auction.end_at.saturating_add(delay);
+                AuctionsExecutionQueue::<T, I>::insert(auction.end_at, auction.auction_id, ());
+                Auctions::<T, I>::insert(auction.auction_id, auction);
+            }
+
+            Ok(scanned)
+        }
+
+        /// Halts trading if the unit price of a trade strays too far from the tier's index.
+        fn check_circuit_breaker(
+            auction_id: T::AuctionId,
+            tier: &Tier,
+            clearing_price: T::Price,
+            quantity: T::Quantity,
+        ) {
+            let threshold = T::CircuitBreakerThreshold::get();
+            if threshold.is_zero() {
+                return;
+            }
+            let Some(index) = PriceIndices::<T, I>::get(tier.level) else { return };
+
+            let unit_price = Self::unit_price(clearing_price, quantity);
+            let reference_price: u128 = index.time_weighted.saturated_into();
+            let deviation = unit_price.max(reference_price) - unit_price.min(reference_price);
+
+            if deviation > threshold.mul_floor(reference_price) {
+                Self::halt_market();
+                Self::deposit_event(Event::CircuitBreakerTripped {
+                    auction_id,
+                    tier_level: tier.level,
+                    unit_price: T::Price::from(unit_price),
+                    reference_price: index.time_weighted,
+                });
+            }
+        }
+
+        /// The delivery period, i.e. the hour of the day, of an auction ending at `end_at`.
+        pub fn delivery_period(end_at: BlockNumberFor<T>) -> u32 {
+            let length = T::DeliveryPeriodLength::get().max(One::one());
+            (end_at / length).saturated_into::<u32>() % DELIVERY_PERIODS
+        }
+
+        /// The price limits per kWh of a tier in a delivery period.
+        pub fn price_limit(tier_level: u32, delivery_period: u32) -> PriceLimit<T::Price> {
+            PeriodPriceLimits::<T, I>::get(tier_level, delivery_period)
+                .or_else(|| TierPriceLimits::<T, I>::get(tier_level))
+                .unwrap_or_default()
+        }
+
+        /// The price limits of an auction's whole lot.
+        fn lot_price_limit(auction_data: &AuctionDataOf<T, I>) -> PriceLimit<T::Price> {
+            let limit = Self::price_limit(
+                auction_data.auction_category.level,
+                auction_data.delivery_period,
             );
+            let quantity: u128 = auction_data.quantity.saturated_into();
+            let scale = |price: T::Price| {
+                T::Price::from(price.saturated_into::<u128>().saturating_mul(quantity))
+            };
 
-            let mut auction_data = Auctions::<T, I>::get(auction_id).expect("data of auction");
+            PriceLimit { floor: limit.floor.map(scale), cap: limit.cap.map(scale) }
+        }
+
+        fn ensure_starting_price_within_limits(
+            auction_data: &AuctionDataOf<T, I>,
+        ) -> DispatchResult {
+            let limit = Self::lot_price_limit(auction_data);
+            let price = auction_data.starting_bid.bid;
 
             ensure!(
-                matches!(auction_data.auction_status, AuctionStatus::Open),
-                Error::<T, I>::AuctionIsOver
+                limit.floor.map_or(true, |floor| price >= floor),
+                Error::<T, I>::StartingPriceBelowFloor
+            );
+            ensure!(
+                limit.cap.map_or(true, |cap| price <= cap),
+                Error::<T, I>::StartingPriceAboveCap
             );
 
-            auction_data.auction_status = AuctionStatus::Closed;
+            Ok(())
+        }
 
-            Auctions::<T, I>::remove(auction_data.auction_id);
+        /// The open auctions an account takes part in with the given role, ordered by id.
+        pub fn auctions_of(who: &T::AccountId, party_type: PartyType) -> AuctionInfoOf<T, I> {
+            let auction_ids: Vec<T::AuctionId> = match party_type {
+                PartyType::Seller => SellerAuctions::<T, I>::iter_key_prefix(who).collect(),
+                PartyType::Buyer => BuyerAuctions::<T, I>::iter_key_prefix(who).collect(),
+            };
 
-            let mut sellers_auction_info =
-                AuctionsOf::<T, I>::get(auction_data.seller_id.clone()).expect("info of seller");
+            let mut auctions: Vec<AuctionDataOf<T, I>> =
+                auction_ids.into_iter().filter_map(Auctions::<T, I>::get).collect();
+            auctions.sort_by_key(|auction| auction.auction_id);
 
-            let index = sellers_auction_info
-                .auctions
-                .iter()
-                .position(|x| x.auction_id.clone() == auction_id)
-                .expect("index of auction");
+            AuctionInfo { participant_id: Some(who.clone()), party_type, auctions }
+        }
 
-            sellers_auction_info.auctions.remove(index);
-            AuctionsOf::<T, I>::insert(auction_data.seller_id.clone(), sellers_auction_info);
+        /// A page of the auctions an account took part in, oldest first.
+        pub fn account_history(
+            who: &T::AccountId,
+            page: u32,
+            page_size: u32,
+        ) -> Vec<(T::AuctionId, PartyType)> {
+            let start = page.saturating_mul(page_size);
+            let end = start.saturating_add(page_size).min(AuctionHistoryCount::<T, I>::get(who));
+
+            (start..end)
+                .filter_map(|index| AuctionHistory::<T, I>::get(who, index))
+                .collect()
+        }
 
-            AuctionsExecutionQueue::<T, I>::remove(auction_data.end_at, auction_data.auction_id);
+        /// A page of the public auctions still accepting bids, ordered by id and optionally
+        /// restricted to one tier.
+        pub fn open_auctions(
+            tier: Option<u32>,
+            page: u32,
+            page_size: u32,
+        ) -> Vec<AuctionDataOf<T, I>> {
+            let mut auctions: Vec<AuctionDataOf<T, I>> = Auctions::<T, I>::iter_values()
+                .filter(|auction| Self::is_open(auction))
+                .filter(|auction| !AuctionAccess::<T, I>::contains_key(auction.auction_id))
+                .filter(|auction| {
+                    tier.map_or(true, |level| auction.auction_category.level == level)
+                })
+                .collect();
+            auctions.sort_by_key(|auction| auction.auction_id);
+
+            auctions
+                .into_iter()
+                .skip(page.saturating_mul(page_size) as usize)
+                .take(page_size as usize)
+                .collect()
+        }
 
-            Self::deposit_event(Event::AuctionCanceled {
-                auction_id: auction_data.auction_id,
-                seller_id: auction_data.seller_id,
-                energy_quantity: auction_data.quantity,
-                starting_price: auction_data.starting_bid.bid,
+        /// The best bid an account holds in each auction it is bidding in.
+        pub fn bids_of(who: &T::AccountId) -> Vec<(T::AuctionId, Bid<T::AccountId, T::Price>)> {
+            let mut bids: Vec<(T::AuctionId, Bid<T::AccountId, T::Price>)> =
+                BuyerAuctions::<T, I>::iter_key_prefix(who)
+                    .filter_map(|auction_id| {
+                        let auction = Auctions::<T, I>::get(auction_id)?;
+                        let bid = auction.bids.into_iter().find(|bid| &bid.bidder == who)?;
+                        Some((auction_id, bid))
+                    })
+                    .collect();
+            bids.sort_by_key(|(auction_id, _)| *auction_id);
+            bids
+        }
+
+        pub fn highest_bid(auction_id: T::AuctionId) -> Option<Bid<T::AccountId, T::Price>> {
+            Auctions::<T, I>::get(auction_id).map(|auction| auction.highest_bid)
+        }
+
+        /// What bidding `bid` on an auction would reserve, and the fee taken if the bid wins.
+        pub fn quote_bid(
+            auction_id: T::AuctionId,
+            bid: T::Price,
+        ) -> Option<BidQuote<T::Price, BalanceOf<T, I>>> {
+            let auction = Auctions::<T, I>::get(auction_id)?;
+            let reserved = Self::price_to_balance(bid);
+            let fee = Self::trade_fee(&auction.auction_category).mul_floor(reserved);
+
+            Some(BidQuote {
+                highest_bid: auction.highest_bid.bid,
+                accepted: Self::is_open(&auction) && bid > auction.highest_bid.bid,
+                reserved,
+                fee,
+                seller_receives: reserved.saturating_sub(fee),
+            })
+        }
+
+        /// Blocks left until an auction closes, zero once it is waiting to be executed.
+        pub fn time_remaining(auction_id: T::AuctionId) -> Option<BlockNumberFor<T>> {
+            let now = <frame_system::Pallet<T>>::block_number();
+            Auctions::<T, I>::get(auction_id).map(|auction| auction.end_at.saturating_sub(now))
+        }
+
+        /// The trades kept for a tier's price index, oldest first.
+        pub fn price_history(
+            tier_level: u32,
+        ) -> Vec<PriceObservation<T::Price, T::Quantity, BlockNumberFor<T>>> {
+            let mut observations = PriceObservations::<T, I>::get(tier_level).into_inner();
+            let cursor = PriceObservationCursor::<T, I>::get(tier_level) as usize;
+            if cursor < observations.len() {
+                observations.rotate_left(cursor);
+            }
+            observations
+        }
+
+        /// Time-weighted average unit price of a tier over the last `window` blocks. A trade's
+        /// price holds until the next trade of the tier, and counts for at least one block.
+        pub fn time_weighted_price(tier_level: u32, window: BlockNumberFor<T>) -> Option<T::Price> {
+            let now = <frame_system::Pallet<T>>::block_number();
+            let start = now.saturating_sub(window);
+            let observations = Self::price_history(tier_level);
+
+            let mut weighted: u128 = 0;
+            let mut blocks: u128 = 0;
+            for (index, observation) in observations.iter().enumerate() {
+                let until = match observations.get(index + 1) {
+                    Some(next) if next.executed_at <= start => continue,
+                    Some(next) => next.executed_at,
+                    None => now,
+                };
+                let from = observation.executed_at.max(start);
+                let duration = until.saturating_sub(from).saturated_into::<u128>().max(1);
+
+                weighted = weighted.saturating_add(
+                    Self::unit_price(observation.clearing_price, observation.quantity)
+                        .saturating_mul(duration),
+                );
+                blocks = blocks.saturating_add(duration);
+            }
+
+            (blocks > 0).then(|| T::Price::from(weighted / blocks))
+        }
+
+        /// Volume-weighted average unit price of the trades of a tier in the last `window`
+        /// blocks.
+        pub fn volume_weighted_price(
+            tier_level: u32,
+            window: BlockNumberFor<T>,
+        ) -> Option<T::Price> {
+            let start = <frame_system::Pallet<T>>::block_number().saturating_sub(window);
+
+            let (value, volume) = Self::price_history(tier_level)
+                .into_iter()
+                .filter(|observation| observation.executed_at >= start)
+                .fold((0u128, 0u128), |(value, volume), observation| {
+                    (
+                        value.saturating_add(observation.clearing_price.saturated_into()),
+                        volume.saturating_add(observation.quantity.saturated_into()),
+                    )
+                });
+
+            (volume > 0).then(|| T::Price::from(value / volume))
+        }
+
+        fn unit_price(clearing_price: T::Price, quantity: T::Quantity) -> u128 {
+            clearing_price.saturated_into::<u128>() / quantity.saturated_into::<u128>().max(1)
+        }
+
+        /// Adds a trade to its tier's ring buffer and recomputes the tier's index.
+        fn record_price(
+            tier: &Tier,
+            clearing_price: T::Price,
+            quantity: T::Quantity,
+            now: BlockNumberFor<T>,
+        ) {
+            let capacity = T::MaxPriceObservations::get();
+            if capacity == 0 {
+                return;
+            }
+
+            let observation = PriceObservation { clearing_price, quantity, executed_at: now };
+            PriceObservations::<T, I>::mutate(tier.level, |observations| {
+                if let Err(observation) = observations.try_push(observation) {
+                    let cursor = PriceObservationCursor::<T, I>::get(tier.level);
+                    if let Some(slot) = observations.get_mut(cursor as usize) {
+                        *slot = observation;
+                    }
+                    PriceObservationCursor::<T, I>::insert(tier.level, (cursor + 1) % capacity);
+                }
             });
 
-            Ok(())
+            let window = T::PriceIndexWindow::get();
+            if let (Some(time_weighted), Some(volume_weighted)) = (
+                Self::time_weighted_price(tier.level, window),
+                Self::volume_weighted_price(tier.level, window),
+            ) {
+                PriceIndices::<T, I>::insert(
+                    tier.level,
+                    PriceIndexValue { time_weighted, volume_weighted, updated_at: now },
+                );
+            }
         }
 
-        #[pallet::call_index(2)]
-        #[pallet::weight(100_000_000)]
-        pub fn bid(origin: OriginFor<T>, auction_id: T::AuctionId, bid: u128) -> DispatchResult {
-            let buyer_id = ensure_signed(origin)?;
+        pub(crate) fn append_history(
+            who: &T::AccountId,
+            auction_id: T::AuctionId,
+            party_type: PartyType,
+        ) {
+            AuctionHistoryCount::<T, I>::mutate(who, |count| {
+                AuctionHistory::<T, I>::insert(who, *count, (auction_id, party_type));
+                *count = count.saturating_add(1);
+            });
+        }
 
-            ensure!(
-                Auctions::<T, I>::contains_key(auction_id),
-                Error::<T, I>::AuctionDoesNotExist
+        /// Drops a closed auction from the seller and buyer indexes, along with its auto bids and
+        /// buyer restriction. History is kept. Returns the number of auto bids removed.
+        fn remove_from_indexes(auction_data: &AuctionDataOf<T, I>) -> u32 {
+            SellerAuctions::<T, I>::remove(&auction_data.seller_id, auction_data.auction_id);
+            for bid in auction_data.bids.iter() {
+                BuyerAuctions::<T, I>::remove(&bid.bidder, auction_data.auction_id);
+            }
+            let auto_bids = AutoBids::<T, I>::clear_prefix(
+                auction_data.auction_id,
+                T::MaxAutoBids::get(),
+                None,
             );
+            AuctionAccess::<T, I>::remove(auction_data.auction_id);
+            auto_bids.unique
+        }
 
-            let mut auction_data = Auctions::<T, I>::get(auction_id).expect("data of auction");
+        /// Refunds the bid deposits of a closed auction and drops its bids from the bidders'
+        /// live bid counts.
+        fn release_bid_deposits(auction_data: &AuctionDataOf<T, I>) {
+            for (bidder, deposit) in BidDeposits::<T, I>::drain_prefix(auction_data.auction_id) {
+                T::Currency::unreserve(&bidder, deposit);
+                let count = auction_data.bids.iter().filter(|bid| bid.bidder == bidder).count();
+                Self::drop_live_bids(&bidder, count as u32);
+            }
+        }
 
-            ensure!(
-                matches!(auction_data.auction_status, AuctionStatus::Open),
-                Error::<T, I>::AuctionIsOver
-            );
+        fn refund_bid_deposit(auction_id: T::AuctionId, bidder: &T::AccountId) {
+            if let Some(deposit) = BidDeposits::<T, I>::take(auction_id, bidder) {
+                T::Currency::unreserve(bidder, deposit);
+            }
+        }
 
-            let new_bid = Bid::<T::AccountId, T::Price> {
-                bidder: buyer_id.clone(),
-                bid: bid.into(),
-            };
+        fn drop_live_bids(bidder: &T::AccountId, count: u32) {
+            LiveBids::<T, I>::mutate_exists(bidder, |live| {
+                *live = live.map(|n| n.saturating_sub(count)).filter(|n| *n > 0);
+            });
+        }
 
-            if new_bid.bid > auction_data.bids[0].bid {
+        /// Converts an auction period in minutes into blocks of six seconds.
+        fn period_to_blocks(auction_period: u16) -> BlockNumberFor<T> {
+            (u32::from(auction_period) * 60 / 6).into()
+        }
 
-                auction_data.bids.insert(0, new_bid.clone());
-                auction_data.highest_bid = new_bid.clone();
-                Auctions::<T, I>::insert(auction_data.auction_id, auction_data.clone());
+        fn tier_of(energy_quantity: u128) -> Tier {
+            if energy_quantity < T::LargeTierThreshold::get() {
+                Tier::default()
+            } else {
+                Tier { level: 2 }
             }
+        }
 
-            let buyer_auction_info = AuctionsOf::<T, I>::get(buyer_id.clone());
+        /// The account holding the market's fees.
+        pub fn account_id() -> T::AccountId {
+            T::PalletId::get().into_account_truncating()
+        }
 
-            match buyer_auction_info {
+        /// Creates the treasury account with the existential deposit if it does not exist yet,
+        /// so that small fees are never lost as dust. The deposit is newly minted and added to
+        /// the total issuance. Returns whether it had to be funded.
+        pub fn ensure_treasury() -> bool {
+            let treasury = Self::account_id();
+            let min = T::Currency::minimum_balance();
+            let free = T::Currency::free_balance(&treasury);
+            if free >= min {
+                return false;
+            }
+            // Dropping the positive imbalance raises the total issuance by the minted amount.
+            let minted = T::Currency::deposit_creating(&treasury, min.saturating_sub(free));
+            drop(minted);
+            true
+        }
 
-                Some(mut auction_info) => {
-                    for (index, auction) in auction_info.auctions.clone().into_iter().enumerate() {
+        /// The fee rate applied to trades of the given tier.
+        pub fn trade_fee(tier: &Tier) -> Perbill {
+            TierTradeFee::<T, I>::get(tier.level).unwrap_or_else(T::TradeFee::get)
+        }
 
-                        if auction_info.auctions.len() >= 5 {
-                            auction_info.auctions.pop();
-                        }
+        /// The account's reputation score, with decay applied up to the current block.
+        pub fn reputation_score(who: &T::AccountId) -> u32 {
+            let reputation = Reputations::<T, I>::get(who);
+            Self::decayed_score(&reputation, <frame_system::Pallet<T>>::block_number())
+        }
 
-                        if auction.auction_id == auction_id {
+        fn decayed_score(
+            reputation: &Reputation<T::Quantity, BlockNumberFor<T>>,
+            now: BlockNumberFor<T>,
+        ) -> u32 {
+            let period = T::ReputationDecayPeriod::get();
+            if period.is_zero() {
+                return reputation.score;
+            }
 
-                            auction_info.auctions.insert(index, auction_data.clone());
+            let periods = (now.saturating_sub(reputation.updated_at) / period)
+                .saturated_into::<u32>()
+                .min(MAX_DECAY_PERIODS);
+            let retention = T::ReputationRetention::get();
 
-                            AuctionsOf::<T, I>::insert(
-                                &buyer_id,
-                                AuctionInfo {
-                                    participant_id: Some(buyer_id.clone()),
-                                    party_type: PartyType::Seller,
-                                    auctions: auction_info.auctions.clone(),
-                                },
-                            )
-                        }
-                    }
+            let mut score = reputation.score;
+            for _ in 0..periods {
+                if score == 0 {
+                    break;
                 }
+                score = retention.mul_floor(score);
+            }
+            score
+        }
+
+        fn update_reputation(
+            who: &T::AccountId,
+            f: impl FnOnce(&mut Reputation<T::Quantity, BlockNumberFor<T>>),
+        ) {
+            let now = <frame_system::Pallet<T>>::block_number();
+            Reputations::<T, I>::mutate(who, |reputation| {
+                reputation.score = Self::decayed_score(reputation, now);
+                reputation.updated_at = now;
+                f(reputation);
+            });
+        }
 
-                None => {
+        pub(crate) fn price_to_balance(price: T::Price) -> BalanceOf<T, I> {
+            price.saturated_into::<u128>().saturated_into()
+        }
 
-                    let mut auction_info =
-                        AuctionsOf::<T, I>::get(buyer_id.clone()).unwrap_or(AuctionInfo {
-                            participant_id: None,
-                            party_type: PartyType::Seller,
-                            auctions: vec![],
-                        });
+        /// Unreserves a standing bid. The seller's starting bid holds no funds.
+        fn release_bid(seller: &T::AccountId, bid: &Bid<T::AccountId, T::Price>) {
+            if &bid.bidder != seller {
+                T::Currency::unreserve(&bid.bidder, Self::price_to_balance(bid.bid));
+            }
+        }
 
-                    auction_info.auctions.push(auction_data.clone());
+        /// Moves the trade fee from the buyer's reserved bid to the treasury and the rest to
+        /// the seller, where it stays reserved until the dispute window has passed. Returns the
+        /// fee charged.
+        ///
+        /// Only what was actually moved out of the buyer's reserve is escrowed and charged. If
+        /// the reserve fell short, the missing part is reported in `SettlementShortfall` and the
+        /// buyer earns no trade points.
+        fn settle(
+            auction_id: T::AuctionId,
+            seller: &T::AccountId,
+            bid: &Bid<T::AccountId, T::Price>,
+            quantity: T::Quantity,
+            tier: &Tier,
+        ) -> BalanceOf<T, I> {
+            if &bid.bidder == seller {
+                return Zero::zero();
+            }
 
-                    AuctionsOf::<T, I>::insert(
-                        &buyer_id,
-                        AuctionInfo {
-                            participant_id: Some(buyer_id.clone()),
-                            party_type: PartyType::Seller,
-                            auctions: auction_info.auctions.clone(),
-                        },
-                    )
+            let value = Self::price_to_balance(bid.bid);
+            let fee = Self::trade_fee(tier).mul_floor(value);
+            let amount = value.saturating_sub(fee);
+
+            let fee_unpaid = T::Currency::repatriate_reserved(
+                &bid.bidder,
+                &Self::account_id(),
+                fee,
+                BalanceStatus::Free,
+            )
+            .unwrap_or(fee);
+            let amount_unpaid = T::Currency::repatriate_reserved(
+                &bid.bidder,
+                seller,
+                amount,
+                BalanceStatus::Reserved,
+            )
+            .unwrap_or(amount);
+            let fee = fee.saturating_sub(fee_unpaid);
+            let amount = amount.saturating_sub(amount_unpaid);
+            let shortfall = fee_unpaid.saturating_add(amount_unpaid);
+
+            Self::update_reputation(seller, |reputation| {
+                reputation.completed_trades = reputation.completed_trades.saturating_add(1);
+                reputation.contracted_quantity =
+                    reputation.contracted_quantity.saturating_add(quantity);
+                reputation.delivered_quantity =
+                    reputation.delivered_quantity.saturating_add(quantity);
+                reputation.score = reputation.score.saturating_add(TRADE_POINTS);
+            });
+            Self::update_reputation(&bid.bidder, |reputation| {
+                reputation.completed_trades = reputation.completed_trades.saturating_add(1);
+                if shortfall.is_zero() {
+                    reputation.score = reputation.score.saturating_add(TRADE_POINTS);
                 }
+            });
+
+            if !shortfall.is_zero() {
+                Self::deposit_event(Event::SettlementShortfall {
+                    auction_id,
+                    buyer_id: bid.bidder.clone(),
+                    shortfall,
+                });
             }
 
-            let mut seller_auction_info =
-                AuctionsOf::<T, I>::get(auction_data.clone().seller_id).expect("info of seller");
+            let window = T::DisputeWindow::get();
+            if window.is_zero() || amount.is_zero() {
+                T::Currency::unreserve(seller, amount);
+            } else {
+                let executed_at = <frame_system::Pallet<T>>::block_number();
+                let release_at = executed_at + window;
+                Escrows::<T, I>::insert(
+                    auction_id,
+                    Escrow {
+                        seller_id: seller.clone(),
+                        buyer_id: bid.bidder.clone(),
+                        quantity,
+                        amount,
+                        executed_at,
+                        release_at,
+                    },
+                );
+                EscrowReleaseQueue::<T, I>::insert(release_at, auction_id, ());
+            }
 
-            for (index, auction) in seller_auction_info.auctions.clone().into_iter().enumerate() {
+            fee
+        }
 
-                if seller_auction_info.auctions.len() >= 5 {
-                    seller_auction_info.auctions.pop();
-                }
+        fn release_escrow(auction_id: T::AuctionId) {
+            if let Some(escrow) = Escrows::<T, I>::take(auction_id) {
+                T::Currency::unreserve(&escrow.seller_id, escrow.amount);
 
-                if auction.auction_id == auction_id {
+                Self::deposit_event(Event::EscrowReleased {
+                    auction_id,
+                    seller_id: escrow.seller_id,
+                    amount: escrow.amount,
+                });
+            }
+        }
 
-                    seller_auction_info
-                        .auctions
-                        .insert(index, auction_data.clone());
+        fn release_listing_deposit(auction_data: &AuctionDataOf<T, I>) {
+            if let Some(deposit) = ListingDeposits::<T, I>::take(auction_data.auction_id) {
+                T::Currency::unreserve(&auction_data.seller_id, deposit);
+            }
+        }
 
-                    AuctionsOf::<T, I>::insert(
-                        &buyer_id,
-                        AuctionInfo {
-                            participant_id: Some(buyer_id.clone()),
-                            party_type: PartyType::Seller,
-                            auctions: seller_auction_info.auctions.clone(),
-                        },
-                    )
+        /// Relists an auction nobody bid on at a discounted starting price, or closes it
+        /// without a trade once no relist rounds are left.
+        /// Relists an auction nobody bid on, or closes it. Returns the number of auto bids
+        /// removed.
+        fn on_auction_unsold(mut auction_data: AuctionDataOf<T, I>) -> u32 {
+            let now = <frame_system::Pallet<T>>::block_number();
+            let auction_id = auction_data.auction_id;
+            let rounds = RelistRounds::<T, I>::take(auction_id).unwrap_or_default();
+
+            if rounds > 0 && !auction_data.auction_period.is_zero() {
+                auction_data.start_at = now;
+                auction_data.end_at = now + auction_data.auction_period;
+
+                // The discount stops at the floor of the auction's delivery period.
+                let price = auction_data.starting_bid.bid;
+                let discounted = price.saturating_sub(T::RelistDiscount::get().mul_floor(price));
+                let floor = Self::lot_price_limit(&auction_data).floor.unwrap_or_default();
+                let starting_bid = Bid::<T::AccountId, T::Price> {
+                    bidder: auction_data.seller_id.clone(),
+                    bid: discounted.max(floor),
+                };
+
+                auction_data.starting_bid = starting_bid.clone();
+                auction_data.bids = vec![starting_bid.clone()];
+                auction_data.highest_bid = starting_bid;
+
+                let rounds_left = rounds - 1;
+                if rounds_left > 0 {
+                    RelistRounds::<T, I>::insert(auction_id, rounds_left);
                 }
+
+                AuctionsExecutionQueue::<T, I>::insert(auction_data.end_at, auction_id, ());
+                Auctions::<T, I>::insert(auction_id, auction_data.clone());
+
+                Self::deposit_event(Event::AuctionRelisted {
+                    auction_id,
+                    seller_id: auction_data.seller_id,
+                    energy_quantity: auction_data.quantity,
+                    starting_price: auction_data.starting_bid.bid,
+                    end_at: auction_data.end_at,
+                    rounds_left,
+                });
+                return 0;
             }
 
-            Auctions::<T, I>::insert(&auction_data.auction_id, auction_data.clone());
+            Auctions::<T, I>::remove(auction_id);
+            let auto_bids = Self::remove_from_indexes(&auction_data);
+            Self::release_bid_deposits(&auction_data);
+            Self::release_listing_deposit(&auction_data);
 
-            Self::deposit_event(Event::AuctionBidAdded {
-                auction_id: auction_data.auction_id,
+            Self::deposit_event(Event::AuctionExpired {
+                auction_id,
                 seller_id: auction_data.seller_id,
                 energy_quantity: auction_data.quantity,
-                bid: new_bid,
+                starting_price: auction_data.starting_bid.bid,
+                expired_at: now,
             });
 
-            Ok(())
+            auto_bids
         }
-    }
 
-    impl<T: Config<I>, I: 'static> Pallet<T, I> {
-        fn on_auction_ended(auction_id: T::AuctionId) {
+        /// Executes or expires an auction that has ended. Returns the weight consumed, including
+        /// that of the `OnAuctionExecuted` handler.
+        fn on_auction_ended(auction_id: T::AuctionId) -> Weight {
+            let auction_data = Auctions::<T, I>::get(auction_id).unwrap();
+            let bids = auction_data.bids.len() as u32;
+
+            if auction_data.highest_bid.bidder == auction_data.seller_id {
+                let auto_bids = Self::on_auction_unsold(auction_data);
+                return T::WeightInfo::expire_auction(bids, auto_bids);
+            }
 
-            let auction_data = Auctions::<T, I>::take(auction_id).unwrap();
+            Auctions::<T, I>::remove(auction_id);
             let now = <frame_system::Pallet<T>>::block_number();
 
+            let auto_bids = Self::remove_from_indexes(&auction_data);
+            Self::release_bid_deposits(&auction_data);
+            Self::release_listing_deposit(&auction_data);
+            RelistRounds::<T, I>::remove(auction_id);
+
+            let fee = Self::settle(
+                auction_data.auction_id,
+                &auction_data.seller_id,
+                &auction_data.highest_bid,
+                auction_data.quantity,
+                &auction_data.auction_category,
+            );
+
+            Settlements::<T, I>::insert(
+                auction_data.auction_id,
+                Settlement {
+                    seller_id: auction_data.seller_id.clone(),
+                    buyer_id: auction_data.highest_bid.bidder.clone(),
+                    quantity: auction_data.quantity,
+                    clearing_price: auction_data.highest_bid.bid,
+                    fee,
+                    auction_category: auction_data.auction_category.clone(),
+                    executed_at: now,
+                },
+            );
+
+            Self::check_circuit_breaker(
+                auction_data.auction_id,
+                &auction_data.auction_category,
+                auction_data.highest_bid.bid,
+                auction_data.quantity,
+            );
+            Self::record_price(
+                &auction_data.auction_category,
+                auction_data.highest_bid.bid,
+                auction_data.quantity,
+                now,
+            );
+
+            let retention = T::SettlementRetention::get();
+            if !retention.is_zero() {
+                SettlementPruningQueue::<T, I>::insert(
+                    now + retention,
+                    auction_data.auction_id,
+                    (),
+                );
+            }
+
+            let hook_weight = T::OnAuctionExecuted::on_auction_executed(
+                auction_data.auction_id,
+                &auction_data.seller_id,
+                &auction_data.highest_bid.bidder,
+                auction_data.quantity,
+                auction_data.highest_bid.bid,
+            );
+
             Self::deposit_event(Event::AuctionMatched {
                 auction_id: auction_data.auction_id,
                 seller_id: auction_data.seller_id.clone(),
@@ -555,8 +3226,68 @@ pub mod pallet {
                 energy_quantity: auction_data.quantity,
                 starting_price: auction_data.starting_bid.bid,
                 highest_bid: auction_data.highest_bid.bid,
+                fee,
                 executed_at: now,
             });
+
+            hook_weight.saturating_add(T::WeightInfo::expire_auction(bids, auto_bids))
+        }
+    }
+
+    impl<T: Config<I>, I: 'static> PriceIndex<T::Price, BlockNumberFor<T>> for Pallet<T, I> {
+        fn current_index(tier_level: u32) -> Option<PriceIndexValue<T::Price, BlockNumberFor<T>>> {
+            PriceIndices::<T, I>::get(tier_level)
+        }
+
+        fn time_weighted_price(tier_level: u32, window: BlockNumberFor<T>) -> Option<T::Price> {
+            Self::time_weighted_price(tier_level, window)
+        }
+
+        fn volume_weighted_price(tier_level: u32, window: BlockNumberFor<T>) -> Option<T::Price> {
+            Self::volume_weighted_price(tier_level, window)
+        }
+    }
+
+    impl<T: Config<I>, I: 'static>
+        EnergyMarket<T::AccountId, T::AuctionId, T::Quantity, T::Price, BlockNumberFor<T>>
+        for Pallet<T, I>
+    {
+        fn create_auction(
+            seller: &T::AccountId,
+            quantity: T::Quantity,
+            starting_price: T::Price,
+            auction_period: u16,
+            min_bidder_reputation: Option<u32>,
+        ) -> Result<(T::AuctionId, Weight), DispatchError> {
+            with_storage_layer(|| {
+                Self::do_create_auction(
+                    seller,
+                    quantity,
+                    starting_price,
+                    auction_period,
+                    min_bidder_reputation,
+                )
+            })
+        }
+
+        fn place_bid(
+            bidder: &T::AccountId,
+            auction_id: T::AuctionId,
+            bid: T::Price,
+        ) -> DispatchResult {
+            with_storage_layer(|| {
+                Self::do_place_bid(bidder, auction_id, bid, false)?;
+                Self::resolve_auto_bids(auction_id);
+                Ok(())
+            })
+        }
+
+        fn auction_info(auction_id: T::AuctionId) -> Option<AuctionDataOf<T, I>> {
+            Auctions::<T, I>::get(auction_id)
+        }
+
+        fn highest_bid(auction_id: T::AuctionId) -> Option<Bid<T::AccountId, T::Price>> {
+            Self::highest_bid(auction_id)
         }
     }
 }
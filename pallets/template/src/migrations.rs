@@ -0,0 +1,258 @@
+//! Storage migrations for the energy bidding pallet.
+//!
+//! Every change to the layout of a stored type bumps `STORAGE_VERSION` and adds a module here
+//! holding the previous layout and an `OnRuntimeUpgrade` translating it. The runtime lists the
+//! migrations in its `Executive`, so auctions that are open during an upgrade keep running.
+
+use super::*;
+use frame_support::{
+    pallet_prelude::*,
+    storage_alias,
+    traits::{Currency, GetStorageVersion, OnRuntimeUpgrade, ReservableCurrency, StorageVersion},
+};
+use frame_system::pallet_prelude::BlockNumberFor;
+use scale_info::prelude::vec::Vec;
+
+#[cfg(feature = "try-runtime")]
+use frame_support::sp_runtime::TryRuntimeError;
+#[cfg(feature = "try-runtime")]
+use scale_info::prelude::collections::BTreeMap;
+
+const LOG_TARGET: &str = "runtime::energy-bidding";
+
+/// Version 2 brings the storage of version 1 to its current layout:
+///
+/// - `AuctionData` gains the minimum bidder reputation and the delivery period it was listed for.
+/// - Highest bids are reserved, since settlement pays the seller out of the buyer's reserve.
+/// - The per-account copies in `AuctionsOf` are replaced by the `SellerAuctions` and
+///   `BuyerAuctions` indexes and the paged `AuctionHistory`.
+/// - The treasury account is created, as trade fees are paid into it.
+pub mod v2 {
+    use super::*;
+
+    #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+    pub struct OldAuctionData<AccountId, AuctionId, Bid, BlockNumber, Quantity, Tier> {
+        pub auction_id: AuctionId,
+        pub seller_id: AccountId,
+        pub quantity: Quantity,
+        pub starting_bid: Bid,
+        pub bids: Vec<Bid>,
+        pub auction_period: BlockNumber,
+        pub auction_status: AuctionStatus,
+        pub start_at: BlockNumber,
+        pub end_at: BlockNumber,
+        pub highest_bid: Bid,
+        pub auction_category: Tier,
+    }
+
+    impl<AccountId, AuctionId, Bid, BlockNumber, Quantity, Tier>
+        OldAuctionData<AccountId, AuctionId, Bid, BlockNumber, Quantity, Tier>
+    {
+        fn migrate(
+            self,
+            delivery_period: u32,
+        ) -> AuctionData<AccountId, AuctionId, Bid, BlockNumber, Quantity, Tier> {
+            AuctionData {
+                auction_id: self.auction_id,
+                seller_id: self.seller_id,
+                quantity: self.quantity,
+                starting_bid: self.starting_bid,
+                bids: self.bids,
+                auction_period: self.auction_period,
+                auction_status: self.auction_status,
+                start_at: self.start_at,
+                end_at: self.end_at,
+                highest_bid: self.highest_bid,
+                auction_category: self.auction_category,
+                min_bidder_reputation: None,
+                delivery_period,
+            }
+        }
+    }
+
+    #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+    pub struct OldAuctionInfo<AccountId, AuctionId, Bid, BlockNumber, Tier, PartyType, Quantity> {
+        pub participant_id: Option<AccountId>,
+        pub party_type: PartyType,
+        pub auctions: Vec<OldAuctionData<AccountId, AuctionId, Bid, BlockNumber, Quantity, Tier>>,
+    }
+
+    pub type OldAuctionDataOf<T, I> = OldAuctionData<
+        <T as frame_system::Config>::AccountId,
+        <T as Config<I>>::AuctionId,
+        Bid<<T as frame_system::Config>::AccountId, <T as Config<I>>::Price>,
+        BlockNumberFor<T>,
+        <T as Config<I>>::Quantity,
+        Tier,
+    >;
+
+    pub type OldAuctionInfoOf<T, I> = OldAuctionInfo<
+        <T as frame_system::Config>::AccountId,
+        <T as Config<I>>::AuctionId,
+        Bid<<T as frame_system::Config>::AccountId, <T as Config<I>>::Price>,
+        BlockNumberFor<T>,
+        Tier,
+        PartyType,
+        <T as Config<I>>::Quantity,
+    >;
+
+    /// Per-account copies of auction data, removed by this migration.
+    #[storage_alias]
+    pub type AuctionsOf<T: Config<I>, I: 'static> = StorageMap<
+        Pallet<T, I>,
+        Twox64Concat,
+        <T as frame_system::Config>::AccountId,
+        OldAuctionInfoOf<T, I>,
+    >;
+
+    /// Reserves the highest bid of an auction listed before bids were reserved. Bids that can
+    /// no longer be funded are dropped, falling back to the next one as `retract_bid` does. The
+    /// seller's starting bid holds no funds and ends the search. Returns the number of
+    /// reserves attempted.
+    fn reserve_highest_bid<T: Config<I>, I: 'static>(auction: &mut AuctionDataOf<T, I>) -> u64 {
+        let mut attempts = 0u64;
+        while auction.bids[0].bidder != auction.seller_id {
+            attempts += 1;
+            let candidate = &auction.bids[0];
+            let value = Pallet::<T, I>::price_to_balance(candidate.bid);
+            if T::Currency::reserve(&candidate.bidder, value).is_ok() {
+                break;
+            }
+            let dropped = auction.bids.remove(0);
+            log::warn!(
+                target: LOG_TARGET,
+                "dropped an unfunded bid of {:?} on auction {:?}",
+                dropped.bidder,
+                auction.auction_id
+            );
+        }
+        auction.highest_bid = auction.bids[0].clone();
+        attempts
+    }
+
+    /// Adds the auction to the index and history of its seller and of every bidder. Returns the
+    /// number of reads and writes made.
+    fn index_auction<T: Config<I>, I: 'static>(auction: &AuctionDataOf<T, I>) -> (u64, u64) {
+        let (mut reads, mut writes) = (1u64, 3u64);
+
+        SellerAuctions::<T, I>::insert(&auction.seller_id, auction.auction_id, ());
+        Pallet::<T, I>::append_history(&auction.seller_id, auction.auction_id, PartyType::Seller);
+
+        for bid in auction.bids.iter() {
+            if bid.bidder == auction.seller_id ||
+                BuyerAuctions::<T, I>::contains_key(&bid.bidder, auction.auction_id)
+            {
+                continue
+            }
+            BuyerAuctions::<T, I>::insert(&bid.bidder, auction.auction_id, ());
+            Pallet::<T, I>::append_history(&bid.bidder, auction.auction_id, PartyType::Buyer);
+            reads += 2;
+            writes += 3;
+        }
+
+        (reads, writes)
+    }
+
+    pub struct MigrateToV2<T, I = ()>(PhantomData<(T, I)>);
+
+    impl<T: Config<I>, I: 'static> OnRuntimeUpgrade for MigrateToV2<T, I> {
+        fn on_runtime_upgrade() -> Weight {
+            let on_chain_version = Pallet::<T, I>::on_chain_storage_version();
+            if on_chain_version != 1 {
+                log::info!(
+                    target: LOG_TARGET,
+                    "skipping v2 migration, on-chain storage version is {:?}",
+                    on_chain_version
+                );
+                return T::DbWeight::get().reads(1)
+            }
+
+            let mut translated = 0u64;
+            let mut reads = 1u64;
+            let mut writes = 1u64;
+
+            crate::Auctions::<T, I>::translate::<OldAuctionDataOf<T, I>, _>(|_, old| {
+                translated += 1;
+                // Auctions could not be postponed before this version, so the end block gives
+                // the period they were priced in.
+                let delivery_period = Pallet::<T, I>::delivery_period(old.end_at);
+                let mut auction = old.migrate(delivery_period);
+                // Highest bids placed before this version hold no funds.
+                let reserves = reserve_highest_bid::<T, I>(&mut auction);
+                let (index_reads, index_writes) = index_auction::<T, I>(&auction);
+                reads += 1 + reserves + index_reads;
+                writes += 1 + reserves + index_writes;
+                Some(auction)
+            });
+
+            let removed = AuctionsOf::<T, I>::clear(u32::MAX, None);
+            writes += u64::from(removed.unique);
+
+            // The treasury was only created at genesis, so chains started before trade fees
+            // need it created here, which also updates the total issuance.
+            let funded = Pallet::<T, I>::ensure_treasury() as u64;
+            reads += 1 + funded;
+            writes += 2 * funded;
+
+            StorageVersion::new(2).put::<Pallet<T, I>>();
+
+            log::info!(target: LOG_TARGET, "migrated {} auctions to v2", translated);
+
+            T::DbWeight::get().reads_writes(reads, writes)
+        }
+
+        #[cfg(feature = "try-runtime")]
+        fn pre_upgrade() -> Result<Vec<u8>, TryRuntimeError> {
+            let auctions = crate::Auctions::<T, I>::iter_keys().count() as u32;
+
+            Ok(auctions.encode())
+        }
+
+        #[cfg(feature = "try-runtime")]
+        fn post_upgrade(state: Vec<u8>) -> Result<(), TryRuntimeError> {
+            let auctions: u32 =
+                Decode::decode(&mut &state[..]).map_err(|_| "pre_upgrade state does not decode")?;
+
+            ensure!(
+                Pallet::<T, I>::on_chain_storage_version() >= 2,
+                "storage version was not updated"
+            );
+            ensure!(
+                crate::Auctions::<T, I>::iter().count() as u32 == auctions,
+                "auctions were lost during the migration"
+            );
+            ensure!(AuctionsOf::<T, I>::iter_keys().next().is_none(), "AuctionsOf was not removed");
+            ensure!(
+                T::Currency::free_balance(&Pallet::<T, I>::account_id()) >=
+                    T::Currency::minimum_balance(),
+                "the treasury account does not exist"
+            );
+
+            let mut held = BTreeMap::<T::AccountId, BalanceOf<T, I>>::new();
+            for (auction_id, auction) in crate::Auctions::<T, I>::iter() {
+                ensure!(
+                    auction.delivery_period < DELIVERY_PERIODS,
+                    "an auction has no valid delivery period"
+                );
+                ensure!(
+                    SellerAuctions::<T, I>::contains_key(&auction.seller_id, auction_id),
+                    "an auction is missing from the seller index"
+                );
+                if auction.highest_bid.bidder == auction.seller_id {
+                    continue
+                }
+                let value = Pallet::<T, I>::price_to_balance(auction.highest_bid.bid);
+                let total = held.entry(auction.highest_bid.bidder).or_default();
+                *total = total.saturating_add(value);
+            }
+            for (bidder, total) in held {
+                ensure!(
+                    T::Currency::reserved_balance(&bidder) >= total,
+                    "a highest bid is not covered by the bidder's reserve"
+                );
+            }
+
+            Ok(())
+        }
+    }
+}
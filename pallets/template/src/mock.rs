@@ -1,9 +1,16 @@
 use crate as energy_bidding;
-use frame_support::traits::{ConstU16, ConstU64};
+use energy_bidding::Instance2;
+use frame_support::{
+    parameter_types,
+    traits::{ConstU128, ConstU16, ConstU32, ConstU64, Hooks},
+    weights::Weight,
+    PalletId,
+};
+use frame_system::EnsureRoot;
 use sp_core::H256;
 use sp_runtime::{
-    BuildStorage,
-    traits::{BlakeTwo256, IdentityLookup}
+    traits::{AccountIdConversion, BlakeTwo256, IdentityLookup},
+    AccountId32, BuildStorage, Perbill,
 };
 
 // type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
@@ -20,11 +27,13 @@ pub type AccountId = <<Signature as Verify>::Signer as IdentifyAccount>::Account
 
 // Configure a mock runtime to test the pallet.
 frame_support::construct_runtime!(
-	pub enum Test
-	{
-		System: frame_system,
-		EnergyBiddingModule: energy_bidding,
-	}
+    pub enum Test
+    {
+        System: frame_system,
+        Balances: pallet_balances,
+        EnergyBiddingModule: energy_bidding,
+        IntradayMarket: energy_bidding::<Instance2>,
+    }
 );
 
 impl frame_system::Config for Test {
@@ -34,17 +43,17 @@ impl frame_system::Config for Test {
     type DbWeight = ();
     type RuntimeOrigin = RuntimeOrigin;
     type RuntimeCall = RuntimeCall;
-	type Nonce = u64;
+    type Nonce = u64;
     type Hash = H256;
     type Hashing = BlakeTwo256;
     type AccountId = AccountId;
     type Lookup = IdentityLookup<Self::AccountId>;
-	type Block = Block;
+    type Block = Block;
     type RuntimeEvent = RuntimeEvent;
     type BlockHashCount = ConstU64<250>;
     type Version = ();
     type PalletInfo = PalletInfo;
-    type AccountData = ();
+    type AccountData = pallet_balances::AccountData<u128>;
     type OnNewAccount = ();
     type OnKilledAccount = ();
     type SystemWeightInfo = ();
@@ -79,15 +88,223 @@ impl frame_system::Config for Test {
 // 	type MaxConsumers = frame_support::traits::ConstU32<16>;
 // }
 
+impl pallet_balances::Config for Test {
+    type MaxLocks = ();
+    type MaxReserves = ();
+    type ReserveIdentifier = [u8; 8];
+    type Balance = u128;
+    type RuntimeEvent = RuntimeEvent;
+    type DustRemoval = ();
+    type ExistentialDeposit = ConstU128<1>;
+    type AccountStore = System;
+    type WeightInfo = ();
+    type FreezeIdentifier = ();
+    type MaxFreezes = ();
+    type RuntimeHoldReason = ();
+    type MaxHolds = ();
+}
+
+parameter_types! {
+    pub const EnergyBiddingPalletId: PalletId = PalletId(*b"py/enbid");
+    pub const IntradayPalletId: PalletId = PalletId(*b"py/intra");
+    pub const IntradayTradeFee: Perbill = Perbill::from_percent(2);
+    pub const CircuitBreakerThreshold: Perbill = Perbill::from_percent(100);
+    pub const NoCircuitBreaker: Perbill = Perbill::from_percent(0);
+    pub const TradeFee: Perbill = Perbill::from_percent(1);
+    pub const ReputationRetention: Perbill = Perbill::from_percent(50);
+    pub const RetractionPenalty: Perbill = Perbill::from_percent(10);
+    pub const RelistDiscount: Perbill = Perbill::from_percent(10);
+    pub const MaxMarketHookWeight: Weight = HOOK_WEIGHT;
+    pub static MarketHookCalls: Vec<(&'static str, u64)> = vec![];
+}
+
+pub const HOOK_WEIGHT: Weight = Weight::from_parts(1_000, 0);
+
+/// Records the market hooks it receives, in order.
+pub struct RecordMarketHooks;
+
+impl RecordMarketHooks {
+    fn record(hook: &'static str, auction_id: u64) -> Weight {
+        MarketHookCalls::mutate(|calls| calls.push((hook, auction_id)));
+        HOOK_WEIGHT
+    }
+}
+
+impl energy_bidding::OnAuctionCreated<AccountId, u64, u128, u128> for RecordMarketHooks {
+    fn on_auction_created(auction_id: u64, _: &AccountId, _: u128, _: u128) -> Weight {
+        Self::record("created", auction_id)
+    }
+}
+
+impl energy_bidding::OnAuctionCanceled<AccountId, u64, u128, u128> for RecordMarketHooks {
+    fn on_auction_canceled(auction_id: u64, _: &AccountId, _: u128, _: u128) -> Weight {
+        Self::record("canceled", auction_id)
+    }
+}
+
+impl energy_bidding::OnAuctionExecuted<AccountId, u64, u128, u128> for RecordMarketHooks {
+    fn on_auction_executed(
+        auction_id: u64,
+        _: &AccountId,
+        _: &AccountId,
+        _: u128,
+        _: u128,
+    ) -> Weight {
+        Self::record("executed", auction_id)
+    }
+}
+
+pub const LISTING_FEE: u128 = 10;
+pub const LISTING_DEPOSIT: u128 = 100;
+pub const INITIAL_BALANCE: u128 = 1_000_000;
+pub const DISPUTE_WINDOW: u64 = 10;
+pub const REPUTATION_DECAY_PERIOD: u64 = 100;
+pub const SETTLEMENT_RETENTION: u64 = 100;
+pub const PRICE_INDEX_WINDOW: u64 = 100;
+pub const BID_DEPOSIT: u128 = 5;
+pub const SPONSORSHIP_PERIOD: u64 = 100;
+pub const DELIVERY_PERIOD_LENGTH: u64 = 600;
+
 impl energy_bidding::Config for Test {
-	type RuntimeEvent = RuntimeEvent;
+    type RuntimeEvent = RuntimeEvent;
+    type AuctionId = u64;
+    type Quantity = u128;
+    type Price = u128;
+    type Currency = Balances;
+    type PalletId = EnergyBiddingPalletId;
+    type TradeFee = TradeFee;
+    type ListingFee = ConstU128<LISTING_FEE>;
+    type TreasuryOrigin = EnsureRoot<AccountId>;
+    type DisputeWindow = ConstU64<DISPUTE_WINDOW>;
+    type ArbitratorOrigin = EnsureRoot<AccountId>;
+    type ReputationRetention = ReputationRetention;
+    type ReputationDecayPeriod = ConstU64<REPUTATION_DECAY_PERIOD>;
+    type MaxBidsPerAuction = ConstU32<10>;
+    type MaxLiveBids = ConstU32<100>;
+    type MaxAutoBids = ConstU32<3>;
+    type MaxGroupMembers = ConstU32<4>;
+    type BidDeposit = ConstU128<0>;
+    type RetractionPenalty = RetractionPenalty;
+    type SettlementRetention = ConstU64<SETTLEMENT_RETENTION>;
+    type ListingDeposit = ConstU128<LISTING_DEPOSIT>;
+    type RelistDiscount = RelistDiscount;
+    type MaxRelistRounds = ConstU32<2>;
+    type MaxProcessedPerBlock = ConstU32<2>;
+    type MaxBlocksScannedPerBlock = ConstU32<10>;
+    type DeliveryPeriodLength = ConstU64<DELIVERY_PERIOD_LENGTH>;
+    type MaxBatchSize = ConstU32<8>;
+    type MaxPriceObservations = ConstU32<3>;
+    type PriceIndexWindow = ConstU64<PRICE_INDEX_WINDOW>;
+    type MaxAuctionPeriod = ConstU16<1_440>;
+    type LargeTierThreshold = ConstU128<5>;
+    type RegulatorOrigin = EnsureRoot<AccountId>;
+    type PauseOrigin = EnsureRoot<AccountId>;
+    type CircuitBreakerThreshold = CircuitBreakerThreshold;
+    type SponsorshipPeriod = ConstU64<SPONSORSHIP_PERIOD>;
+    type SponsoredCallsPerPeriod = ConstU32<2>;
+    type MaxSponsoredFailures = ConstU32<2>;
+    type OnAuctionCreated = RecordMarketHooks;
+    type OnAuctionCanceled = RecordMarketHooks;
+    type MaxMarketHookWeight = MaxMarketHookWeight;
+    type OnAuctionExecuted = (RecordMarketHooks, ());
+    type WeightInfo = ();
+}
+
+impl energy_bidding::Config<Instance2> for Test {
+    type RuntimeEvent = RuntimeEvent;
     type AuctionId = u64;
     type Quantity = u128;
     type Price = u128;
-	// type WeightInfo = ();
+    type Currency = Balances;
+    type PalletId = IntradayPalletId;
+    type TradeFee = IntradayTradeFee;
+    type ListingFee = ConstU128<LISTING_FEE>;
+    type TreasuryOrigin = EnsureRoot<AccountId>;
+    type DisputeWindow = ConstU64<DISPUTE_WINDOW>;
+    type ArbitratorOrigin = EnsureRoot<AccountId>;
+    type ReputationRetention = ReputationRetention;
+    type ReputationDecayPeriod = ConstU64<REPUTATION_DECAY_PERIOD>;
+    type MaxBidsPerAuction = ConstU32<2>;
+    type MaxLiveBids = ConstU32<3>;
+    type MaxAutoBids = ConstU32<3>;
+    type MaxGroupMembers = ConstU32<4>;
+    type BidDeposit = ConstU128<BID_DEPOSIT>;
+    type RetractionPenalty = RetractionPenalty;
+    type SettlementRetention = ConstU64<SETTLEMENT_RETENTION>;
+    type ListingDeposit = ConstU128<LISTING_DEPOSIT>;
+    type RelistDiscount = RelistDiscount;
+    type MaxRelistRounds = ConstU32<0>;
+    type MaxProcessedPerBlock = ConstU32<2>;
+    type MaxBlocksScannedPerBlock = ConstU32<10>;
+    type DeliveryPeriodLength = ConstU64<DELIVERY_PERIOD_LENGTH>;
+    type MaxBatchSize = ConstU32<8>;
+    type MaxPriceObservations = ConstU32<3>;
+    type PriceIndexWindow = ConstU64<PRICE_INDEX_WINDOW>;
+    type MaxAuctionPeriod = ConstU16<60>;
+    type LargeTierThreshold = ConstU128<2>;
+    type RegulatorOrigin = EnsureRoot<AccountId>;
+    type PauseOrigin = EnsureRoot<AccountId>;
+    type CircuitBreakerThreshold = NoCircuitBreaker;
+    type SponsorshipPeriod = ConstU64<SPONSORSHIP_PERIOD>;
+    type SponsoredCallsPerPeriod = ConstU32<2>;
+    type MaxSponsoredFailures = ConstU32<2>;
+    type OnAuctionCreated = ();
+    type OnAuctionCanceled = ();
+    type MaxMarketHookWeight = ();
+    type OnAuctionExecuted = ();
+    type WeightInfo = ();
+}
+
+pub fn alice() -> AccountId {
+    AccountId32::from(*b"000000000000000000000ALICE000000")
+}
+
+pub fn bob() -> AccountId {
+    AccountId32::from(*b"000000000000000000000BOB00000000")
+}
+
+pub fn charlie() -> AccountId {
+    AccountId32::from(*b"000000000000000000000CHARLIE0000")
+}
+
+pub fn treasury() -> AccountId {
+    EnergyBiddingPalletId::get().into_account_truncating()
+}
+
+pub fn intraday_treasury() -> AccountId {
+    IntradayPalletId::get().into_account_truncating()
+}
+
+/// Runs the pallet's hooks for every block up to and including `n`.
+pub fn run_to_block(n: u64) {
+    while System::block_number() < n {
+        System::set_block_number(System::block_number() + 1);
+        EnergyBiddingModule::on_initialize(System::block_number());
+        IntradayMarket::on_initialize(System::block_number());
+    }
 }
 
 // Build genesis storage according to the mock runtime.
 pub fn new_test_ext() -> sp_io::TestExternalities {
-	frame_system::GenesisConfig::<Test>::default().build_storage().unwrap().into()
+    let mut t = frame_system::GenesisConfig::<Test>::default().build_storage().unwrap();
+
+    pallet_balances::GenesisConfig::<Test> {
+        balances: vec![
+            (alice(), INITIAL_BALANCE),
+            (bob(), INITIAL_BALANCE),
+            (charlie(), INITIAL_BALANCE),
+        ],
+    }
+    .assimilate_storage(&mut t)
+    .unwrap();
+
+    energy_bidding::GenesisConfig::<Test> { auction_index: 0 }
+        .assimilate_storage(&mut t)
+        .unwrap();
+
+    energy_bidding::GenesisConfig::<Test, Instance2> { auction_index: 0 }
+        .assimilate_storage(&mut t)
+        .unwrap();
+
+    t.into()
 }
@@ -0,0 +1,157 @@
+//! Fee-less market calls for registered small prosumers.
+//!
+//! [`SponsorMarketFees`] wraps the extension that charges transaction fees, usually
+//! `ChargeTransactionPayment`. Calls into the market instance `I` made by a registered small
+//! prosumer with quota left skip the inner extension, so no fee is charged. Everything else is
+//! passed through unchanged. The wrapper encodes like the inner extension and reports its
+//! metadata, so clients build transactions as before.
+
+use crate::{Call, Config, Pallet};
+use codec::{Decode, Encode};
+use frame_support::{
+    sp_runtime::{
+        traits::{DispatchInfoOf, PostDispatchInfoOf, SignedExtension, SignedExtensionMetadata},
+        transaction_validity::{TransactionValidity, TransactionValidityError, ValidTransaction},
+        DispatchResult,
+    },
+    sp_std::{fmt, marker::PhantomData, vec::Vec},
+    traits::IsSubType,
+};
+use frame_system::pallet_prelude::BlockNumberFor;
+use scale_info::TypeInfo;
+
+/// Waives the fees of market calls made by registered small prosumers, charging all other
+/// transactions through the wrapped extension `S`.
+#[derive(Encode, Decode, Clone, Eq, PartialEq, TypeInfo)]
+#[scale_info(skip_type_params(T, I))]
+pub struct SponsorMarketFees<T, I, S>(pub S, PhantomData<(T, I)>);
+
+impl<T, I, S> SponsorMarketFees<T, I, S> {
+    pub fn new(inner: S) -> Self {
+        Self(inner, PhantomData)
+    }
+}
+
+impl<T, I, S> From<S> for SponsorMarketFees<T, I, S> {
+    fn from(inner: S) -> Self {
+        Self::new(inner)
+    }
+}
+
+impl<T, I, S: fmt::Debug> fmt::Debug for SponsorMarketFees<T, I, S> {
+    #[cfg(feature = "std")]
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "SponsorMarketFees({:?})", self.0)
+    }
+
+    #[cfg(not(feature = "std"))]
+    fn fmt(&self, _: &mut fmt::Formatter) -> fmt::Result {
+        Ok(())
+    }
+}
+
+impl<T, I, S> SponsorMarketFees<T, I, S>
+where
+    T: Config<I>,
+    I: 'static,
+    <T as frame_system::Config>::RuntimeCall: IsSubType<Call<T, I>>,
+{
+    /// The quota slot a call by `who` is sponsored with, if any.
+    fn sponsored_slot(
+        who: &T::AccountId,
+        call: &<T as frame_system::Config>::RuntimeCall,
+    ) -> Option<(BlockNumberFor<T>, u32)> {
+        let market_call: Option<&Call<T, I>> = call.is_sub_type();
+        market_call.and_then(|_| Pallet::<T, I>::sponsored_slot(who))
+    }
+}
+
+impl<T, I, S> SignedExtension for SponsorMarketFees<T, I, S>
+where
+    T: Config<I> + Send + Sync,
+    I: 'static + Clone + Eq + Send + Sync,
+    <T as frame_system::Config>::RuntimeCall: IsSubType<Call<T, I>>,
+    S: SignedExtension<AccountId = T::AccountId, Call = <T as frame_system::Config>::RuntimeCall>,
+{
+    const IDENTIFIER: &'static str = S::IDENTIFIER;
+    type AccountId = T::AccountId;
+    type Call = <T as frame_system::Config>::RuntimeCall;
+    type AdditionalSigned = S::AdditionalSigned;
+    /// The signer, and the inner extension's `Pre` unless the fee was sponsored.
+    type Pre = (T::AccountId, Option<S::Pre>);
+
+    fn additional_signed(&self) -> Result<Self::AdditionalSigned, TransactionValidityError> {
+        self.0.additional_signed()
+    }
+
+    fn validate(
+        &self,
+        who: &Self::AccountId,
+        call: &Self::Call,
+        info: &DispatchInfoOf<Self::Call>,
+        len: usize,
+    ) -> TransactionValidity {
+        if let Some(slot) = Self::sponsored_slot(who, call) {
+            // Sponsored calls pay no tip and get the lowest priority. They provide the quota
+            // slot they take, so the pool holds one free call of a signer at a time, and only
+            // while quota is left. Past the quota the call is validated as a paying one.
+            return ValidTransaction::with_tag_prefix("SponsorMarketFees")
+                .and_provides((who, slot))
+                .build();
+        }
+        self.0.validate(who, call, info, len)
+    }
+
+    fn pre_dispatch(
+        self,
+        who: &Self::AccountId,
+        call: &Self::Call,
+        info: &DispatchInfoOf<Self::Call>,
+        len: usize,
+    ) -> Result<Self::Pre, TransactionValidityError> {
+        if Self::sponsored_slot(who, call).is_some() {
+            Pallet::<T, I>::use_sponsorship(who);
+            return Ok((who.clone(), None));
+        }
+        Ok((who.clone(), Some(self.0.pre_dispatch(who, call, info, len)?)))
+    }
+
+    fn validate_unsigned(
+        call: &Self::Call,
+        info: &DispatchInfoOf<Self::Call>,
+        len: usize,
+    ) -> TransactionValidity {
+        S::validate_unsigned(call, info, len)
+    }
+
+    fn pre_dispatch_unsigned(
+        call: &Self::Call,
+        info: &DispatchInfoOf<Self::Call>,
+        len: usize,
+    ) -> Result<(), TransactionValidityError> {
+        S::pre_dispatch_unsigned(call, info, len)
+    }
+
+    fn post_dispatch(
+        pre: Option<Self::Pre>,
+        info: &DispatchInfoOf<Self::Call>,
+        post_info: &PostDispatchInfoOf<Self::Call>,
+        len: usize,
+        result: &DispatchResult,
+    ) -> Result<(), TransactionValidityError> {
+        match pre {
+            Some((who, None)) => {
+                if result.is_err() {
+                    Pallet::<T, I>::record_sponsored_failure(&who);
+                }
+                Ok(())
+            },
+            Some((_, inner)) => S::post_dispatch(inner, info, post_info, len, result),
+            None => S::post_dispatch(None, info, post_info, len, result),
+        }
+    }
+
+    fn metadata() -> Vec<SignedExtensionMetadata> {
+        S::metadata()
+    }
+}
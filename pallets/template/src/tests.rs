@@ -1,12 +1,24 @@
-use crate::{mock::*, Bid, Event};
-use frame_support::pallet_prelude::Weight;
-use frame_support::{assert_ok, traits::Hooks};
-use sp_runtime::AccountId32;
+use crate::{
+    migrations, mock::*, AuctionStatus, Auctions, AutoBid, Bid, BidQuote, BuyerAccess,
+    EnergyMarket, Error, Event, Instance2, ListingSpec, MarketStatus, PartyType, PriceIndex,
+    PriceIndexValue, PriceLimit, Recurrence, Settlement, SponsorMarketFees, Tier, WeightInfo,
+    TRADE_POINTS,
+};
+use codec::Encode;
+use frame_support::{
+    assert_noop, assert_ok,
+    dispatch::{DispatchInfo, DispatchResult, GetDispatchInfo},
+    storage,
+    traits::{
+        Currency, GetStorageVersion, Hooks, OnRuntimeUpgrade, ReservableCurrency, StorageVersion,
+    },
+    weights::Weight,
+};
+use sp_runtime::{traits::SignedExtension, AccountId32, DispatchError, Perbill};
 
 #[test]
 fn new_bid_should_work() {
     new_test_ext().execute_with(|| {
-
         System::set_block_number(2);
 
         let seller = RuntimeOrigin::signed(AccountId::from(AccountId32::from(
@@ -22,48 +34,76 @@ fn new_bid_should_work() {
             seller,
             energy_quantity,
             starting_price,
-            auction_period
+            auction_period,
+            None,
+            None
         ));
 
         let auction = EnergyBiddingModule::auctions(0).expect("return indexed auction");
 
         assert_eq!(
             auction.seller_id,
-            AccountId::from(AccountId32::from(
-                b"000000000000000000000ALICE000000".clone(),
-            ))
+            AccountId::from(AccountId32::from(b"000000000000000000000ALICE000000".clone(),))
         );
         assert_eq!(auction.quantity, energy_quantity);
         assert_eq!(auction.starting_bid.bid, starting_price);
 
-        let seller_auction_info = EnergyBiddingModule::auctions_of(AccountId::from(
-            AccountId32::from(b"000000000000000000000ALICE000000".clone()),
-        ))
-        .expect("return seller's auction information");
+        let seller_auction_info = EnergyBiddingModule::auctions_of(
+            &AccountId::from(AccountId32::from(b"000000000000000000000ALICE000000".clone())),
+            PartyType::Seller,
+        );
 
         assert_eq!(
             seller_auction_info.participant_id.unwrap(),
             AccountId32::from(b"000000000000000000000ALICE000000".clone())
         );
 
-        assert!(
-            EnergyBiddingModule::auction_execution_queue(execution_block, auction.auction_id)
-                .is_some()
-        );
+        assert!(EnergyBiddingModule::auction_execution_queue(execution_block, auction.auction_id)
+            .is_some());
 
         System::assert_has_event(RuntimeEvent::EnergyBiddingModule(Event::AuctionCreated {
             auction_id: auction.auction_id,
             seller_id: auction.seller_id,
             energy_quantity: auction.quantity,
             starting_price,
+            seller_reputation: 0,
         }));
     })
 }
 
 #[test]
-fn cancel_should_work() {
+fn cancel_by_other_account_should_fail() {
     new_test_ext().execute_with(|| {
+        System::set_block_number(2);
+
+        assert_ok!(EnergyBiddingModule::new(
+            RuntimeOrigin::signed(alice()),
+            2,
+            1_000,
+            5,
+            None,
+            None
+        ));
+        assert_ok!(EnergyBiddingModule::bid(RuntimeOrigin::signed(bob()), 0, 5_000));
+
+        // neither a bidder nor an unrelated account may take the auction down
+        assert_noop!(
+            EnergyBiddingModule::cancel(RuntimeOrigin::signed(bob()), 0),
+            Error::<Test>::NotSeller
+        );
+        assert_noop!(
+            EnergyBiddingModule::cancel(RuntimeOrigin::signed(charlie()), 0),
+            Error::<Test>::NotSeller
+        );
 
+        assert!(EnergyBiddingModule::auctions(0).is_some());
+        assert_eq!(Balances::reserved_balance(bob()), 5_000);
+    });
+}
+
+#[test]
+fn cancel_should_work() {
+    new_test_ext().execute_with(|| {
         System::set_block_number(2);
 
         let seller = RuntimeOrigin::signed(AccountId::from(AccountId32::from(
@@ -79,32 +119,27 @@ fn cancel_should_work() {
             seller.clone(),
             energy_quantity,
             starting_price,
-            auction_period
+            auction_period,
+            None,
+            None
         ));
 
         let auction = EnergyBiddingModule::auctions(0).expect("return indexed auction");
 
-        assert_ok!(EnergyBiddingModule::cancel(
-            seller.clone(),
-            auction.auction_id
-        ));
+        assert_ok!(EnergyBiddingModule::cancel(seller.clone(), auction.auction_id));
 
         assert!(EnergyBiddingModule::auctions(auction.auction_id).is_none());
 
-        assert!(
-            EnergyBiddingModule::auctions_of(AccountId::from(AccountId32::from(
-                b"000000000000000000000ALICE000000".clone(),
-            )))
-            .unwrap()
-            .auctions
-            .get(auction.auction_id as usize)
-            .is_none()
-        );
+        assert!(EnergyBiddingModule::auctions_of(
+            &AccountId::from(AccountId32::from(b"000000000000000000000ALICE000000".clone())),
+            PartyType::Seller,
+        )
+        .auctions
+        .get(auction.auction_id as usize)
+        .is_none());
 
-        assert!(
-            EnergyBiddingModule::auction_execution_queue(execution_block, auction.auction_id)
-                .is_none()
-        );
+        assert!(EnergyBiddingModule::auction_execution_queue(execution_block, auction.auction_id)
+            .is_none());
 
         System::assert_has_event(RuntimeEvent::EnergyBiddingModule(Event::AuctionCanceled {
             auction_id: auction.auction_id,
@@ -115,11 +150,9 @@ fn cancel_should_work() {
     });
 }
 
-
 #[test]
 fn bidding_should_work() {
     new_test_ext().execute_with(|| {
-
         System::set_block_number(2);
 
         let seller = RuntimeOrigin::signed(AccountId::from(AccountId32::from(
@@ -133,7 +166,9 @@ fn bidding_should_work() {
             seller.clone(),
             energy_quantity,
             starting_price,
-            auction_period
+            auction_period,
+            None,
+            None
         ));
 
         let mut auction = EnergyBiddingModule::auctions(0).expect("return indexed auction");
@@ -154,10 +189,10 @@ fn bidding_should_work() {
         );
 
         assert_eq!(
-            EnergyBiddingModule::auctions_of(AccountId::from(AccountId32::from(
-                b"000000000000000000000BOB00000000".clone(),
-            )))
-            .unwrap()
+            EnergyBiddingModule::auctions_of(
+                &AccountId::from(AccountId32::from(b"000000000000000000000BOB00000000".clone())),
+                PartyType::Buyer,
+            )
             .auctions
             .get(auction.auction_id as usize)
             .unwrap()
@@ -166,15 +201,13 @@ fn bidding_should_work() {
             AccountId32::from(b"000000000000000000000BOB00000000".clone())
         );
 
-        assert!(
-            EnergyBiddingModule::auctions_of(AccountId::from(AccountId32::from(
-                b"000000000000000000000ALICE000000".clone(),
-            )))
-            .unwrap()
-            .auctions
-            .get(auction.auction_id as usize)
-            .is_some()
-        );
+        assert!(EnergyBiddingModule::auctions_of(
+            &AccountId::from(AccountId32::from(b"000000000000000000000ALICE000000".clone())),
+            PartyType::Seller,
+        )
+        .auctions
+        .get(auction.auction_id as usize)
+        .is_some());
 
         System::assert_has_event(RuntimeEvent::EnergyBiddingModule(Event::AuctionBidAdded {
             auction_id: auction.auction_id,
@@ -191,7 +224,6 @@ fn bidding_should_work() {
 #[test]
 fn on_bid_ended_should_work() {
     new_test_ext().execute_with(|| {
-
         System::set_block_number(2);
 
         let seller = RuntimeOrigin::signed(AccountId::from(AccountId32::from(
@@ -205,7 +237,9 @@ fn on_bid_ended_should_work() {
             seller.clone(),
             energy_quantity,
             starting_price,
-            auction_period
+            auction_period,
+            None,
+            None
         ));
 
         let mut auction = EnergyBiddingModule::auctions(0).expect("return indexed auction");
@@ -219,20 +253,20 @@ fn on_bid_ended_should_work() {
         assert_ok!(EnergyBiddingModule::bid(buyer.clone(), auction_id, new_bid));
         auction = EnergyBiddingModule::auctions(0).expect("return indexed auction");
 
-        // fast forward block production to a block after auction execution block height
+        // fast forward block production to the block after the auction's last block
         let execution_block = System::block_number() + 50;
-        System::set_block_number(52);
+        System::set_block_number(execution_block + 1);
 
         assert_eq!(
-            EnergyBiddingModule::on_initialize(execution_block),
-            Weight::from_all(100_000_000u64)
+            EnergyBiddingModule::on_initialize(execution_block + 1),
+            <() as WeightInfo>::on_initialize_base() +
+                <() as WeightInfo>::scan_queue() * 4 +
+                <() as WeightInfo>::expire_auction(2, 0) +
+                HOOK_WEIGHT
         );
-        EnergyBiddingModule::on_finalize(execution_block);
 
-        assert!(
-            EnergyBiddingModule::auction_execution_queue(execution_block, auction.auction_id)
-                .is_none()
-        );
+        assert!(EnergyBiddingModule::auction_execution_queue(execution_block, auction.auction_id)
+            .is_none());
 
         System::assert_has_event(RuntimeEvent::EnergyBiddingModule(Event::AuctionMatched {
             auction_id: auction.auction_id,
@@ -250,7 +284,2129 @@ fn on_bid_ended_should_work() {
             energy_quantity: auction.quantity,
             starting_price: auction.starting_bid.bid,
             highest_bid: auction.highest_bid.bid,
+            fee: 100,
             executed_at: System::block_number(),
         }));
     });
-}
\ No newline at end of file
+}
+
+#[test]
+fn settlement_should_pay_seller_and_treasury() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(2);
+
+        assert_ok!(EnergyBiddingModule::new(
+            RuntimeOrigin::signed(alice()),
+            2,
+            1_000,
+            5,
+            None,
+            None
+        ));
+        assert_eq!(Balances::free_balance(treasury()), 1 + LISTING_FEE);
+
+        assert_ok!(EnergyBiddingModule::bid(RuntimeOrigin::signed(bob()), 0, 5_000));
+        assert_eq!(Balances::reserved_balance(bob()), 5_000);
+
+        // a higher bid releases the funds held for the previous one
+        assert_ok!(EnergyBiddingModule::bid(RuntimeOrigin::signed(charlie()), 0, 10_000));
+        assert_eq!(Balances::reserved_balance(bob()), 0);
+        assert_eq!(Balances::reserved_balance(charlie()), 10_000);
+
+        run_to_block(53);
+
+        assert_eq!(Balances::reserved_balance(charlie()), 0);
+        assert_eq!(Balances::free_balance(charlie()), INITIAL_BALANCE - 10_000);
+        assert_eq!(Balances::free_balance(treasury()), 1 + LISTING_FEE + 100);
+
+        // the seller's payment is held until the dispute window has passed
+        assert_eq!(Balances::reserved_balance(alice()), 9_900);
+        assert!(EnergyBiddingModule::escrow_release_queue(53 + DISPUTE_WINDOW, 0).is_some());
+
+        run_to_block(54 + DISPUTE_WINDOW);
+
+        assert!(EnergyBiddingModule::escrows(0).is_none());
+        assert_eq!(Balances::reserved_balance(alice()), 0);
+        assert_eq!(Balances::free_balance(alice()), INITIAL_BALANCE - LISTING_FEE + 9_900);
+        System::assert_last_event(RuntimeEvent::EnergyBiddingModule(Event::EscrowReleased {
+            auction_id: 0,
+            seller_id: alice(),
+            amount: 9_900,
+        }));
+    });
+}
+
+#[test]
+fn settlement_should_only_pay_what_was_reserved() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(2);
+
+        assert_ok!(EnergyBiddingModule::new(
+            RuntimeOrigin::signed(alice()),
+            2,
+            1_000,
+            5,
+            None,
+            None
+        ));
+        assert_ok!(EnergyBiddingModule::bid(RuntimeOrigin::signed(charlie()), 0, 10_000));
+
+        // part of the reserved bid went missing before the auction ended
+        Balances::unreserve(&charlie(), 4_000);
+
+        run_to_block(53);
+
+        assert_eq!(Balances::reserved_balance(charlie()), 0);
+        assert_eq!(Balances::free_balance(treasury()), 1 + LISTING_FEE + 100);
+        assert_eq!(Balances::reserved_balance(alice()), 5_900);
+        assert_eq!(EnergyBiddingModule::escrows(0).unwrap().amount, 5_900);
+        assert_eq!(EnergyBiddingModule::settlements(0).unwrap().fee, 100);
+        assert_eq!(EnergyBiddingModule::reputations(charlie()).completed_trades, 1);
+        assert_eq!(EnergyBiddingModule::reputations(charlie()).score, 0);
+        System::assert_has_event(RuntimeEvent::EnergyBiddingModule(Event::SettlementShortfall {
+            auction_id: 0,
+            buyer_id: charlie(),
+            shortfall: 4_000,
+        }));
+    });
+}
+
+#[test]
+fn tier_trade_fee_should_override_default() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(2);
+
+        assert_noop!(
+            EnergyBiddingModule::set_trade_fee(
+                RuntimeOrigin::signed(alice()),
+                1,
+                Some(Perbill::from_percent(5))
+            ),
+            DispatchError::BadOrigin
+        );
+        assert_ok!(EnergyBiddingModule::set_trade_fee(
+            RuntimeOrigin::root(),
+            1,
+            Some(Perbill::from_percent(5))
+        ));
+
+        assert_ok!(EnergyBiddingModule::new(
+            RuntimeOrigin::signed(alice()),
+            2,
+            1_000,
+            5,
+            None,
+            None
+        ));
+        assert_ok!(EnergyBiddingModule::bid(RuntimeOrigin::signed(bob()), 0, 10_000));
+
+        run_to_block(53);
+
+        System::assert_has_event(RuntimeEvent::EnergyBiddingModule(Event::AuctionExecuted {
+            auction_id: 0,
+            seller_id: alice(),
+            buyer_id: bob(),
+            energy_quantity: 2,
+            starting_price: 1_000,
+            highest_bid: 10_000,
+            fee: 500,
+            executed_at: 53,
+        }));
+    });
+}
+
+#[test]
+fn spend_treasury_should_work() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(2);
+
+        assert_ok!(EnergyBiddingModule::new(
+            RuntimeOrigin::signed(alice()),
+            2,
+            1_000,
+            5,
+            None,
+            None
+        ));
+
+        assert_noop!(
+            EnergyBiddingModule::spend_treasury(RuntimeOrigin::root(), LISTING_FEE + 1, bob()),
+            Error::<Test>::InsufficientTreasuryBalance
+        );
+        assert_ok!(EnergyBiddingModule::spend_treasury(RuntimeOrigin::root(), LISTING_FEE, bob()));
+
+        assert_eq!(Balances::free_balance(bob()), INITIAL_BALANCE + LISTING_FEE);
+        System::assert_last_event(RuntimeEvent::EnergyBiddingModule(Event::TreasurySpent {
+            beneficiary: bob(),
+            amount: LISTING_FEE,
+        }));
+    });
+}
+#[test]
+fn dispute_should_freeze_and_split_settlement() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(2);
+
+        assert_ok!(EnergyBiddingModule::new(
+            RuntimeOrigin::signed(alice()),
+            2,
+            1_000,
+            5,
+            None,
+            None
+        ));
+        assert_ok!(EnergyBiddingModule::bid(RuntimeOrigin::signed(bob()), 0, 10_000));
+
+        run_to_block(53);
+
+        assert_noop!(
+            EnergyBiddingModule::raise_dispute(RuntimeOrigin::signed(charlie()), 0),
+            Error::<Test>::NotBuyer
+        );
+
+        System::set_block_number(55);
+        assert_ok!(EnergyBiddingModule::raise_dispute(RuntimeOrigin::signed(bob()), 0));
+        assert_noop!(
+            EnergyBiddingModule::raise_dispute(RuntimeOrigin::signed(bob()), 0),
+            Error::<Test>::DisputeAlreadyRaised
+        );
+
+        // the escrow is no longer released when the window passes
+        run_to_block(54 + DISPUTE_WINDOW);
+        assert_eq!(Balances::reserved_balance(alice()), 9_900);
+
+        assert_noop!(
+            EnergyBiddingModule::resolve_dispute(
+                RuntimeOrigin::signed(bob()),
+                0,
+                Perbill::from_percent(100)
+            ),
+            DispatchError::BadOrigin
+        );
+        assert_ok!(EnergyBiddingModule::resolve_dispute(
+            RuntimeOrigin::root(),
+            0,
+            Perbill::from_percent(40)
+        ));
+
+        assert_eq!(Balances::reserved_balance(alice()), 0);
+        assert_eq!(Balances::free_balance(alice()), INITIAL_BALANCE - LISTING_FEE + 5_940);
+        assert_eq!(Balances::free_balance(bob()), INITIAL_BALANCE - 10_000 + 3_960);
+
+        let dispute = EnergyBiddingModule::disputes(0).expect("dispute is kept for audit");
+        assert_eq!(dispute.executed_at, 53);
+        assert_eq!(dispute.raised_at, 55);
+        assert_eq!(dispute.resolved_at, Some(54 + DISPUTE_WINDOW));
+        assert_eq!(dispute.buyer_share, Some(Perbill::from_percent(40)));
+
+        assert_noop!(
+            EnergyBiddingModule::resolve_dispute(
+                RuntimeOrigin::root(),
+                0,
+                Perbill::from_percent(40)
+            ),
+            Error::<Test>::DisputeAlreadyResolved
+        );
+    });
+}
+
+#[test]
+fn dispute_should_only_split_what_the_seller_still_holds() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(2);
+
+        assert_ok!(EnergyBiddingModule::new(
+            RuntimeOrigin::signed(alice()),
+            2,
+            1_000,
+            5,
+            None,
+            None
+        ));
+        assert_ok!(EnergyBiddingModule::bid(RuntimeOrigin::signed(bob()), 0, 10_000));
+
+        run_to_block(53);
+        assert_ok!(EnergyBiddingModule::raise_dispute(RuntimeOrigin::signed(bob()), 0));
+
+        // part of the escrowed payment is no longer reserved
+        Balances::unreserve(&alice(), 6_000);
+
+        assert_ok!(EnergyBiddingModule::resolve_dispute(
+            RuntimeOrigin::root(),
+            0,
+            Perbill::from_percent(40)
+        ));
+
+        assert_eq!(Balances::reserved_balance(alice()), 0);
+        assert_eq!(Balances::free_balance(bob()), INITIAL_BALANCE - 10_000 + 3_900);
+        System::assert_last_event(RuntimeEvent::EnergyBiddingModule(Event::DisputeResolved {
+            auction_id: 0,
+            buyer_id: bob(),
+            seller_id: alice(),
+            to_buyer: 3_900,
+            to_seller: 0,
+        }));
+    });
+}
+
+#[test]
+fn dispute_after_window_should_fail() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(2);
+
+        assert_ok!(EnergyBiddingModule::new(
+            RuntimeOrigin::signed(alice()),
+            2,
+            1_000,
+            5,
+            None,
+            None
+        ));
+        assert_ok!(EnergyBiddingModule::bid(RuntimeOrigin::signed(bob()), 0, 10_000));
+
+        run_to_block(54 + DISPUTE_WINDOW);
+        assert_noop!(
+            EnergyBiddingModule::raise_dispute(RuntimeOrigin::signed(bob()), 0),
+            Error::<Test>::EscrowDoesNotExist
+        );
+    });
+}
+
+#[test]
+fn reputation_should_track_trades_and_cancellations() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(2);
+
+        assert_ok!(EnergyBiddingModule::new(
+            RuntimeOrigin::signed(alice()),
+            2,
+            1_000,
+            5,
+            None,
+            None
+        ));
+        assert_ok!(EnergyBiddingModule::new(
+            RuntimeOrigin::signed(alice()),
+            2,
+            1_000,
+            5,
+            None,
+            None
+        ));
+
+        assert_ok!(EnergyBiddingModule::cancel(RuntimeOrigin::signed(alice()), 1));
+
+        assert_ok!(EnergyBiddingModule::bid(RuntimeOrigin::signed(bob()), 0, 10_000));
+
+        run_to_block(53);
+
+        let seller = EnergyBiddingModule::reputations(alice());
+        assert_eq!(seller.completed_trades, 1);
+        assert_eq!(seller.cancellations, 1);
+        assert_eq!(seller.contracted_quantity, 2);
+        assert_eq!(seller.delivered_quantity, 2);
+        assert_eq!(seller.score, TRADE_POINTS);
+
+        assert_ok!(EnergyBiddingModule::raise_dispute(RuntimeOrigin::signed(bob()), 0));
+        assert_ok!(EnergyBiddingModule::resolve_dispute(
+            RuntimeOrigin::root(),
+            0,
+            Perbill::from_percent(50)
+        ));
+
+        let seller = EnergyBiddingModule::reputations(alice());
+        assert_eq!(seller.lost_disputes, 1);
+        assert_eq!(seller.delivered_quantity, 1);
+        assert_eq!(seller.score, 0);
+        assert_eq!(EnergyBiddingModule::reputation_score(&bob()), TRADE_POINTS);
+
+        // scores decay by half every period
+        System::set_block_number(53 + 2 * REPUTATION_DECAY_PERIOD);
+        assert_eq!(EnergyBiddingModule::reputation_score(&bob()), TRADE_POINTS / 4);
+    });
+}
+
+#[test]
+fn reputation_requirements_should_be_enforced() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(2);
+
+        assert_ok!(EnergyBiddingModule::new(
+            RuntimeOrigin::signed(alice()),
+            2,
+            1_000,
+            5,
+            Some(TRADE_POINTS),
+            None
+        ));
+        assert_noop!(
+            EnergyBiddingModule::bid(RuntimeOrigin::signed(bob()), 0, 10_000),
+            Error::<Test>::BidderReputationTooLow
+        );
+
+        assert_ok!(EnergyBiddingModule::new(
+            RuntimeOrigin::signed(alice()),
+            2,
+            1_000,
+            5,
+            None,
+            None
+        ));
+        assert_ok!(EnergyBiddingModule::set_min_seller_reputation(
+            RuntimeOrigin::signed(bob()),
+            Some(1)
+        ));
+        assert_noop!(
+            EnergyBiddingModule::bid(RuntimeOrigin::signed(bob()), 1, 10_000),
+            Error::<Test>::SellerReputationTooLow
+        );
+
+        assert_ok!(EnergyBiddingModule::set_min_seller_reputation(
+            RuntimeOrigin::signed(bob()),
+            None
+        ));
+        assert_ok!(EnergyBiddingModule::bid(RuntimeOrigin::signed(bob()), 1, 10_000));
+    });
+}
+
+#[test]
+fn retract_lower_bid_should_be_free() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(2);
+
+        assert_ok!(EnergyBiddingModule::new(
+            RuntimeOrigin::signed(alice()),
+            2,
+            1_000,
+            5,
+            None,
+            None
+        ));
+        assert_ok!(EnergyBiddingModule::bid(RuntimeOrigin::signed(bob()), 0, 5_000));
+        assert_ok!(EnergyBiddingModule::bid(RuntimeOrigin::signed(charlie()), 0, 10_000));
+
+        assert_noop!(
+            EnergyBiddingModule::retract_bid(RuntimeOrigin::signed(alice()), 0),
+            Error::<Test>::BidDoesNotExist
+        );
+        assert_ok!(EnergyBiddingModule::retract_bid(RuntimeOrigin::signed(bob()), 0));
+
+        let auction = EnergyBiddingModule::auctions(0).unwrap();
+        assert_eq!(auction.bids.len(), 2);
+        assert_eq!(auction.highest_bid.bidder, charlie());
+        assert_eq!(Balances::free_balance(bob()), INITIAL_BALANCE);
+        assert_eq!(Balances::reserved_balance(charlie()), 10_000);
+
+        System::assert_last_event(RuntimeEvent::EnergyBiddingModule(Event::BidRetracted {
+            auction_id: 0,
+            bidder: bob(),
+            penalty: 0,
+            highest_bid: Bid { bidder: charlie(), bid: 10_000 },
+        }));
+    });
+}
+
+#[test]
+fn retract_highest_bid_should_restore_previous_bid() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(2);
+
+        assert_ok!(EnergyBiddingModule::new(
+            RuntimeOrigin::signed(alice()),
+            2,
+            1_000,
+            5,
+            None,
+            None
+        ));
+        assert_ok!(EnergyBiddingModule::bid(RuntimeOrigin::signed(bob()), 0, 5_000));
+        assert_ok!(EnergyBiddingModule::bid(RuntimeOrigin::signed(charlie()), 0, 10_000));
+
+        assert_ok!(EnergyBiddingModule::retract_bid(RuntimeOrigin::signed(charlie()), 0));
+
+        let auction = EnergyBiddingModule::auctions(0).unwrap();
+        assert_eq!(auction.highest_bid, Bid { bidder: bob(), bid: 5_000 });
+        assert_eq!(Balances::reserved_balance(bob()), 5_000);
+        assert_eq!(Balances::reserved_balance(charlie()), 0);
+        assert_eq!(Balances::free_balance(charlie()), INITIAL_BALANCE - 1_000);
+        assert_eq!(Balances::free_balance(treasury()), 1 + LISTING_FEE + 1_000);
+
+        System::assert_last_event(RuntimeEvent::EnergyBiddingModule(Event::BidRetracted {
+            auction_id: 0,
+            bidder: charlie(),
+            penalty: 1_000,
+            highest_bid: Bid { bidder: bob(), bid: 5_000 },
+        }));
+    });
+}
+
+#[test]
+fn retract_highest_bid_should_skip_unfunded_bids() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(2);
+
+        assert_ok!(EnergyBiddingModule::new(
+            RuntimeOrigin::signed(alice()),
+            2,
+            1_000,
+            5,
+            None,
+            None
+        ));
+        assert_ok!(EnergyBiddingModule::bid(RuntimeOrigin::signed(bob()), 0, 5_000));
+        assert_ok!(EnergyBiddingModule::bid(RuntimeOrigin::signed(charlie()), 0, 10_000));
+
+        Balances::make_free_balance_be(&bob(), 1);
+
+        assert_ok!(EnergyBiddingModule::retract_bid(RuntimeOrigin::signed(charlie()), 0));
+
+        let auction = EnergyBiddingModule::auctions(0).unwrap();
+        assert_eq!(auction.bids.len(), 1);
+        assert_eq!(auction.highest_bid, auction.starting_bid);
+        assert_eq!(Balances::reserved_balance(bob()), 0);
+    });
+}
+
+#[test]
+fn amend_should_work() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(2);
+
+        assert_ok!(EnergyBiddingModule::new(
+            RuntimeOrigin::signed(alice()),
+            2,
+            1_000,
+            5,
+            None,
+            None
+        ));
+
+        assert_noop!(
+            EnergyBiddingModule::amend(RuntimeOrigin::signed(bob()), 0, None, Some(2_000), None),
+            Error::<Test>::NotSeller
+        );
+        assert_noop!(
+            EnergyBiddingModule::amend(RuntimeOrigin::signed(alice()), 0, None, None, Some(0)),
+            Error::<Test>::InvalidAuctionPeriod
+        );
+
+        assert_ok!(EnergyBiddingModule::amend(
+            RuntimeOrigin::signed(alice()),
+            0,
+            Some(10),
+            Some(2_000),
+            Some(10)
+        ));
+
+        let auction = EnergyBiddingModule::auctions(0).unwrap();
+        assert_eq!(auction.quantity, 10);
+        assert_eq!(auction.auction_category.level, 2);
+        assert_eq!(auction.starting_bid.bid, 2_000);
+        assert_eq!(auction.highest_bid.bid, 2_000);
+        assert_eq!(auction.bids.len(), 1);
+        assert_eq!(auction.end_at, 102);
+
+        assert!(EnergyBiddingModule::auction_execution_queue(52, 0).is_none());
+        assert!(EnergyBiddingModule::auction_execution_queue(102, 0).is_some());
+        assert_eq!(
+            EnergyBiddingModule::auctions_of(&alice(), PartyType::Seller).auctions,
+            vec![auction]
+        );
+
+        System::assert_last_event(RuntimeEvent::EnergyBiddingModule(Event::AuctionAmended {
+            auction_id: 0,
+            seller_id: alice(),
+            energy_quantity: 10,
+            starting_price: 2_000,
+            end_at: 102,
+        }));
+
+        assert_ok!(EnergyBiddingModule::bid(RuntimeOrigin::signed(bob()), 0, 10_000));
+        assert_noop!(
+            EnergyBiddingModule::amend(RuntimeOrigin::signed(alice()), 0, None, Some(500), None),
+            Error::<Test>::AuctionHasBids
+        );
+    });
+}
+
+#[test]
+fn migration_to_v2_should_keep_open_auctions() {
+    new_test_ext().execute_with(|| {
+        let starting_bid = Bid { bidder: alice(), bid: 1_000u128 };
+        let old = migrations::v2::OldAuctionData {
+            auction_id: 0u64,
+            seller_id: alice(),
+            quantity: 2u128,
+            starting_bid: starting_bid.clone(),
+            bids: vec![starting_bid.clone()],
+            auction_period: 50u64,
+            auction_status: AuctionStatus::Open,
+            start_at: 1_200u64,
+            end_at: 1_250u64,
+            highest_bid: starting_bid,
+            auction_category: Tier::default(),
+        };
+        storage::unhashed::put(&Auctions::<Test>::hashed_key_for(0u64), &old);
+        StorageVersion::new(1).put::<EnergyBiddingModule>();
+        // chains started before version 2 have no treasury account
+        let _ = Balances::make_free_balance_be(&treasury(), 0);
+        let issuance = Balances::total_issuance();
+
+        migrations::v2::MigrateToV2::<Test>::on_runtime_upgrade();
+
+        assert_eq!(EnergyBiddingModule::on_chain_storage_version(), 2);
+        assert_eq!(Balances::free_balance(treasury()), 1);
+        // the treasury's existential deposit is accounted for in the issuance
+        assert_eq!(Balances::total_issuance(), issuance + 1);
+
+        let auction = EnergyBiddingModule::auctions(0).expect("auction survives the upgrade");
+        assert_eq!(auction.seller_id, alice());
+        assert_eq!(auction.end_at, 1_250);
+        assert_eq!(auction.min_bidder_reputation, None);
+        // the auction keeps the delivery period its end block falls into
+        assert_eq!(auction.delivery_period, 2);
+    });
+}
+
+#[test]
+fn migration_to_v2_should_reserve_legacy_highest_bids() {
+    new_test_ext().execute_with(|| {
+        let starting_bid = Bid { bidder: alice(), bid: 1_000u128 };
+        let bob_bid = Bid { bidder: bob(), bid: 5_000u128 };
+        let charlie_bid = Bid { bidder: charlie(), bid: INITIAL_BALANCE + 1 };
+        let legacy =
+            |auction_id: u64, bids: Vec<Bid<AccountId, u128>>| migrations::v2::OldAuctionData {
+                auction_id,
+                seller_id: alice(),
+                quantity: 2u128,
+                starting_bid: starting_bid.clone(),
+                bids: bids.clone(),
+                auction_period: 50u64,
+                auction_status: AuctionStatus::Open,
+                start_at: 2u64,
+                end_at: 52u64,
+                highest_bid: bids[0].clone(),
+                auction_category: Tier::default(),
+            };
+        storage::unhashed::put(
+            &Auctions::<Test>::hashed_key_for(0u64),
+            &legacy(0, vec![bob_bid.clone(), starting_bid.clone()]),
+        );
+        // charlie can no longer fund the legacy bid, so bob's earlier bid takes its place
+        storage::unhashed::put(
+            &Auctions::<Test>::hashed_key_for(1u64),
+            &legacy(1, vec![charlie_bid, bob_bid.clone(), starting_bid.clone()]),
+        );
+        StorageVersion::new(1).put::<EnergyBiddingModule>();
+
+        migrations::v2::MigrateToV2::<Test>::on_runtime_upgrade();
+
+        assert_eq!(Balances::reserved_balance(bob()), 10_000);
+        assert_eq!(Balances::reserved_balance(charlie()), 0);
+        let auction = EnergyBiddingModule::auctions(1).unwrap();
+        assert_eq!(auction.highest_bid, bob_bid);
+        assert_eq!(auction.bids, vec![bob_bid, starting_bid]);
+    });
+}
+
+#[test]
+fn account_indexes_should_follow_auction_lifecycle() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(2);
+
+        assert_ok!(EnergyBiddingModule::new(
+            RuntimeOrigin::signed(alice()),
+            2,
+            1_000,
+            5,
+            None,
+            None
+        ));
+        assert_ok!(EnergyBiddingModule::new(
+            RuntimeOrigin::signed(alice()),
+            2,
+            1_000,
+            5,
+            None,
+            None
+        ));
+        assert_ok!(EnergyBiddingModule::bid(RuntimeOrigin::signed(bob()), 0, 5_000));
+        assert_ok!(EnergyBiddingModule::bid(RuntimeOrigin::signed(bob()), 0, 6_000));
+
+        // the buyer's index lives under the buyer's own key
+        assert!(EnergyBiddingModule::seller_auctions(alice(), 0).is_some());
+        assert!(EnergyBiddingModule::seller_auctions(bob(), 0).is_none());
+        assert!(EnergyBiddingModule::buyer_auctions(bob(), 0).is_some());
+
+        let bought = EnergyBiddingModule::auctions_of(&bob(), PartyType::Buyer);
+        assert_eq!(bought.auctions.len(), 1);
+        assert_eq!(bought.auctions[0].highest_bid.bid, 6_000);
+        assert_eq!(EnergyBiddingModule::auctions_of(&alice(), PartyType::Seller).auctions.len(), 2);
+
+        assert_ok!(EnergyBiddingModule::cancel(RuntimeOrigin::signed(alice()), 1));
+        run_to_block(53);
+
+        assert!(EnergyBiddingModule::auctions_of(&alice(), PartyType::Seller)
+            .auctions
+            .is_empty());
+        assert!(EnergyBiddingModule::auctions_of(&bob(), PartyType::Buyer).auctions.is_empty());
+
+        // history is kept once the auctions are closed
+        assert_eq!(EnergyBiddingModule::auction_history_count(alice()), 2);
+        assert_eq!(
+            EnergyBiddingModule::account_history(&alice(), 0, 1),
+            vec![(0, PartyType::Seller)]
+        );
+        assert_eq!(
+            EnergyBiddingModule::account_history(&alice(), 1, 1),
+            vec![(1, PartyType::Seller)]
+        );
+        assert!(EnergyBiddingModule::account_history(&alice(), 2, 1).is_empty());
+        assert_eq!(
+            EnergyBiddingModule::account_history(&bob(), 0, 10),
+            vec![(0, PartyType::Buyer)]
+        );
+    });
+}
+
+#[test]
+fn migration_to_v2_should_build_indexes() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(2);
+
+        assert_ok!(EnergyBiddingModule::new(
+            RuntimeOrigin::signed(alice()),
+            2,
+            1_000,
+            5,
+            None,
+            None
+        ));
+        assert_ok!(EnergyBiddingModule::bid(RuntimeOrigin::signed(bob()), 0, 5_000));
+
+        // pretend the auction was listed in version 1
+        let auction = EnergyBiddingModule::auctions(0).unwrap();
+        storage::unhashed::put(
+            &Auctions::<Test>::hashed_key_for(0u64),
+            &migrations::v2::OldAuctionData {
+                auction_id: auction.auction_id,
+                seller_id: auction.seller_id,
+                quantity: auction.quantity,
+                starting_bid: auction.starting_bid,
+                bids: auction.bids,
+                auction_period: auction.auction_period,
+                auction_status: auction.auction_status,
+                start_at: auction.start_at,
+                end_at: auction.end_at,
+                highest_bid: auction.highest_bid,
+                auction_category: auction.auction_category,
+            },
+        );
+        let _ = crate::SellerAuctions::<Test>::clear(u32::MAX, None);
+        let _ = crate::BuyerAuctions::<Test>::clear(u32::MAX, None);
+        let _ = crate::AuctionHistory::<Test>::clear(u32::MAX, None);
+        let _ = crate::AuctionHistoryCount::<Test>::clear(u32::MAX, None);
+        migrations::v2::AuctionsOf::<Test>::insert(
+            alice(),
+            migrations::v2::OldAuctionInfo {
+                participant_id: Some(alice()),
+                party_type: PartyType::Seller,
+                auctions: Vec::new(),
+            },
+        );
+        StorageVersion::new(1).put::<EnergyBiddingModule>();
+
+        migrations::v2::MigrateToV2::<Test>::on_runtime_upgrade();
+
+        assert_eq!(EnergyBiddingModule::on_chain_storage_version(), 2);
+        assert!(migrations::v2::AuctionsOf::<Test>::iter_keys().next().is_none());
+        assert!(EnergyBiddingModule::seller_auctions(alice(), 0).is_some());
+        assert!(EnergyBiddingModule::buyer_auctions(bob(), 0).is_some());
+        assert_eq!(
+            EnergyBiddingModule::account_history(&bob(), 0, 10),
+            vec![(0, PartyType::Buyer)]
+        );
+    });
+}
+
+#[test]
+fn settlement_should_be_archived_and_pruned() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(2);
+
+        assert_ok!(EnergyBiddingModule::new(
+            RuntimeOrigin::signed(alice()),
+            2,
+            1_000,
+            5,
+            None,
+            None
+        ));
+        assert_ok!(EnergyBiddingModule::bid(RuntimeOrigin::signed(bob()), 0, 10_000));
+
+        run_to_block(53);
+
+        assert!(EnergyBiddingModule::auctions(0).is_none());
+        assert_eq!(
+            EnergyBiddingModule::settlements(0),
+            Some(Settlement {
+                seller_id: alice(),
+                buyer_id: bob(),
+                quantity: 2,
+                clearing_price: 10_000,
+                fee: 100,
+                auction_category: Tier::default(),
+                executed_at: 53,
+            })
+        );
+
+        run_to_block(53 + SETTLEMENT_RETENTION);
+        assert!(EnergyBiddingModule::settlements(0).is_some());
+
+        run_to_block(54 + SETTLEMENT_RETENTION);
+        assert!(EnergyBiddingModule::settlements(0).is_none());
+        assert!(
+            EnergyBiddingModule::settlement_pruning_queue(53 + SETTLEMENT_RETENTION, 0).is_none()
+        );
+    });
+}
+
+#[test]
+fn auction_without_bids_should_expire() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(2);
+
+        assert_ok!(EnergyBiddingModule::new(
+            RuntimeOrigin::signed(alice()),
+            2,
+            1_000,
+            5,
+            None,
+            None
+        ));
+        assert_eq!(Balances::reserved_balance(alice()), LISTING_DEPOSIT);
+
+        run_to_block(53);
+
+        assert!(EnergyBiddingModule::auctions(0).is_none());
+        assert!(EnergyBiddingModule::settlements(0).is_none());
+        assert_eq!(Balances::reserved_balance(alice()), 0);
+        assert_eq!(Balances::free_balance(alice()), INITIAL_BALANCE - LISTING_FEE);
+        assert_eq!(EnergyBiddingModule::reputations(alice()).completed_trades, 0);
+
+        System::assert_last_event(RuntimeEvent::EnergyBiddingModule(Event::AuctionExpired {
+            auction_id: 0,
+            seller_id: alice(),
+            energy_quantity: 2,
+            starting_price: 1_000,
+            expired_at: 53,
+        }));
+    });
+}
+
+#[test]
+fn unsold_auction_should_be_relisted_at_lower_price() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(2);
+
+        assert_ok!(EnergyBiddingModule::new(
+            RuntimeOrigin::signed(alice()),
+            2,
+            1_000,
+            5,
+            None,
+            None
+        ));
+
+        assert_noop!(
+            EnergyBiddingModule::set_auto_relist(RuntimeOrigin::signed(bob()), 0, 1),
+            Error::<Test>::NotSeller
+        );
+        assert_noop!(
+            EnergyBiddingModule::set_auto_relist(RuntimeOrigin::signed(alice()), 0, 3),
+            Error::<Test>::TooManyRelistRounds
+        );
+        assert_ok!(EnergyBiddingModule::set_auto_relist(RuntimeOrigin::signed(alice()), 0, 1));
+
+        run_to_block(53);
+
+        let auction = EnergyBiddingModule::auctions(0).expect("auction is relisted");
+        assert_eq!(auction.starting_bid.bid, 900);
+        assert_eq!(auction.highest_bid.bid, 900);
+        assert_eq!(auction.start_at, 53);
+        assert_eq!(auction.end_at, 103);
+        assert!(EnergyBiddingModule::auction_execution_queue(103, 0).is_some());
+        assert_eq!(Balances::reserved_balance(alice()), LISTING_DEPOSIT);
+
+        System::assert_last_event(RuntimeEvent::EnergyBiddingModule(Event::AuctionRelisted {
+            auction_id: 0,
+            seller_id: alice(),
+            energy_quantity: 2,
+            starting_price: 900,
+            end_at: 103,
+            rounds_left: 0,
+        }));
+
+        run_to_block(104);
+
+        assert!(EnergyBiddingModule::auctions(0).is_none());
+        assert_eq!(Balances::reserved_balance(alice()), 0);
+        System::assert_last_event(RuntimeEvent::EnergyBiddingModule(Event::AuctionExpired {
+            auction_id: 0,
+            seller_id: alice(),
+            energy_quantity: 2,
+            starting_price: 900,
+            expired_at: 104,
+        }));
+    });
+}
+
+#[test]
+fn expiry_should_be_capped_and_carried_over() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(2);
+
+        for _ in 0..3 {
+            assert_ok!(EnergyBiddingModule::new(
+                RuntimeOrigin::signed(alice()),
+                2,
+                1_000,
+                5,
+                None,
+                None
+            ));
+        }
+        for auction_id in 0..3 {
+            assert_ok!(EnergyBiddingModule::bid(RuntimeOrigin::signed(bob()), auction_id, 5_000));
+        }
+
+        // bids in the last block of an auction still count
+        run_to_block(52);
+        assert_ok!(EnergyBiddingModule::bid(RuntimeOrigin::signed(charlie()), 0, 6_000));
+
+        run_to_block(53);
+        let overdue: Vec<u64> =
+            (0..3).filter(|auction_id| Auctions::<Test>::contains_key(auction_id)).collect();
+        assert_eq!(overdue.len(), 1);
+        assert_eq!(EnergyBiddingModule::expiry_cursor(), Some(52));
+
+        // overdue auctions are frozen until they are processed
+        assert_noop!(
+            EnergyBiddingModule::bid(RuntimeOrigin::signed(charlie()), overdue[0], 7_000),
+            Error::<Test>::AuctionIsOver
+        );
+        assert_noop!(
+            EnergyBiddingModule::cancel(RuntimeOrigin::signed(alice()), overdue[0]),
+            Error::<Test>::AuctionIsOver
+        );
+
+        run_to_block(54);
+        assert!(EnergyBiddingModule::auctions(overdue[0]).is_none());
+        assert!(EnergyBiddingModule::settlements(overdue[0]).is_some());
+        assert_eq!(EnergyBiddingModule::expiry_cursor(), Some(54));
+        assert_eq!(EnergyBiddingModule::settlements(0).map(|s| s.buyer_id), Some(charlie()));
+    });
+}
+
+#[test]
+fn catching_up_should_scan_a_bounded_number_of_blocks() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(2);
+
+        assert_ok!(EnergyBiddingModule::new(
+            RuntimeOrigin::signed(alice()),
+            2,
+            1_000,
+            5,
+            None,
+            None
+        ));
+        assert_ok!(EnergyBiddingModule::bid(RuntimeOrigin::signed(bob()), 0, 5_000));
+        run_to_block(3);
+        assert_eq!(EnergyBiddingModule::expiry_cursor(), Some(3));
+
+        // no blocks were processed for a long stretch
+        System::set_block_number(100);
+        assert_eq!(
+            EnergyBiddingModule::on_initialize(100),
+            <() as WeightInfo>::on_initialize_base() + <() as WeightInfo>::scan_queue() * 40
+        );
+        assert_eq!(EnergyBiddingModule::expiry_cursor(), Some(13));
+        assert!(EnergyBiddingModule::auctions(0).is_some());
+
+        run_to_block(105);
+        assert_eq!(EnergyBiddingModule::expiry_cursor(), Some(63));
+        assert!(EnergyBiddingModule::settlements(0).is_some());
+
+        run_to_block(110);
+        assert_eq!(EnergyBiddingModule::expiry_cursor(), Some(110));
+    });
+}
+
+#[test]
+fn market_queries_should_work() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(2);
+
+        assert_ok!(EnergyBiddingModule::new(
+            RuntimeOrigin::signed(alice()),
+            2,
+            1_000,
+            5,
+            None,
+            None
+        ));
+        assert_ok!(EnergyBiddingModule::new(
+            RuntimeOrigin::signed(alice()),
+            10,
+            1_000,
+            10,
+            None,
+            None
+        ));
+        assert_ok!(EnergyBiddingModule::new(
+            RuntimeOrigin::signed(charlie()),
+            3,
+            1_000,
+            5,
+            None,
+            None
+        ));
+        assert_ok!(EnergyBiddingModule::bid(RuntimeOrigin::signed(bob()), 0, 5_000));
+        assert_ok!(EnergyBiddingModule::bid(RuntimeOrigin::signed(bob()), 1, 6_000));
+
+        let ids = |auctions: Vec<crate::AuctionDataOf<Test>>| -> Vec<u64> {
+            auctions.into_iter().map(|auction| auction.auction_id).collect()
+        };
+        assert_eq!(ids(EnergyBiddingModule::open_auctions(None, 0, 10)), vec![0, 1, 2]);
+        assert_eq!(ids(EnergyBiddingModule::open_auctions(None, 1, 2)), vec![2]);
+        assert_eq!(ids(EnergyBiddingModule::open_auctions(Some(1), 0, 10)), vec![0, 2]);
+        assert_eq!(ids(EnergyBiddingModule::open_auctions(Some(2), 0, 10)), vec![1]);
+
+        assert_eq!(
+            EnergyBiddingModule::bids_of(&bob()),
+            vec![(0, Bid { bidder: bob(), bid: 5_000 }), (1, Bid { bidder: bob(), bid: 6_000 })]
+        );
+        assert_eq!(
+            EnergyBiddingModule::highest_bid(2),
+            Some(Bid { bidder: charlie(), bid: 1_000 })
+        );
+        assert_eq!(EnergyBiddingModule::time_remaining(1), Some(100));
+
+        // auctions past their end are no longer listed as open
+        System::set_block_number(53);
+        assert_eq!(ids(EnergyBiddingModule::open_auctions(None, 0, 10)), vec![1]);
+        assert_eq!(EnergyBiddingModule::time_remaining(0), Some(0));
+        assert_eq!(EnergyBiddingModule::time_remaining(3), None);
+    });
+}
+
+#[test]
+fn quote_bid_should_include_trade_fee() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(2);
+
+        assert_ok!(EnergyBiddingModule::new(
+            RuntimeOrigin::signed(alice()),
+            2,
+            1_000,
+            5,
+            None,
+            None
+        ));
+
+        assert_eq!(
+            EnergyBiddingModule::quote_bid(0, 10_000),
+            Some(BidQuote {
+                highest_bid: 1_000,
+                accepted: true,
+                reserved: 10_000,
+                fee: 100,
+                seller_receives: 9_900,
+            })
+        );
+        assert_eq!(
+            EnergyBiddingModule::quote_bid(0, 1_000).map(|quote| quote.accepted),
+            Some(false)
+        );
+        assert_eq!(EnergyBiddingModule::quote_bid(1, 1_000), None);
+    });
+}
+
+#[test]
+fn price_index_should_follow_executions() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(2);
+
+        // unit prices of 1_000, 2_000, 1_500 and 2_000, executed at 53, 103, 153 and 203
+        let trades = [(2, 5, 2_000), (4, 10, 8_000), (2, 15, 3_000), (2, 20, 4_000)];
+        for (auction_id, (quantity, period, bid)) in trades.into_iter().enumerate() {
+            assert_ok!(EnergyBiddingModule::new(
+                RuntimeOrigin::signed(alice()),
+                quantity,
+                1_000,
+                period,
+                None,
+                None
+            ));
+            assert_ok!(EnergyBiddingModule::bid(
+                RuntimeOrigin::signed(bob()),
+                auction_id as u64,
+                bid
+            ));
+        }
+        assert_eq!(EnergyBiddingModule::current_index(1), None);
+
+        run_to_block(103);
+        assert_eq!(
+            EnergyBiddingModule::current_index(1),
+            Some(PriceIndexValue { time_weighted: 1_019, volume_weighted: 1_666, updated_at: 103 })
+        );
+
+        // the buffer holds three trades, the oldest one is dropped
+        run_to_block(203);
+        let executed_at: Vec<u64> = EnergyBiddingModule::price_history(1)
+            .into_iter()
+            .map(|observation| observation.executed_at)
+            .collect();
+        assert_eq!(executed_at, vec![103, 153, 203]);
+        assert_eq!(
+            EnergyBiddingModule::current_index(1),
+            Some(PriceIndexValue { time_weighted: 1_752, volume_weighted: 1_875, updated_at: 203 })
+        );
+        assert_eq!(EnergyBiddingModule::current_index(2), None);
+
+        assert_eq!(
+            <EnergyBiddingModule as PriceIndex<u128, u64>>::time_weighted_price(1, 50),
+            Some(1_509)
+        );
+        assert_eq!(
+            <EnergyBiddingModule as PriceIndex<u128, u64>>::volume_weighted_price(1, 50),
+            Some(1_750)
+        );
+    });
+}
+
+#[test]
+fn market_hooks_should_be_called() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(2);
+
+        let post_info =
+            EnergyBiddingModule::new(RuntimeOrigin::signed(alice()), 2, 1_000, 5, None, None)
+                .unwrap();
+        // the weight of the creation hook is part of the call's weight
+        assert_eq!(post_info.actual_weight, Some(Weight::from_parts(100_000_000, 0) + HOOK_WEIGHT));
+        let listing = ListingSpec {
+            energy_quantity: 2,
+            starting_price: 1_000,
+            auction_period: 5,
+            min_bidder_reputation: None,
+            buyers: None,
+        };
+        let post_info = EnergyBiddingModule::new_batch(
+            RuntimeOrigin::signed(alice()),
+            vec![listing.clone(), listing],
+        )
+        .unwrap();
+        assert_eq!(
+            post_info.actual_weight,
+            Some(<() as WeightInfo>::new_batch(2) + HOOK_WEIGHT * 2)
+        );
+        assert_ok!(EnergyBiddingModule::bid(RuntimeOrigin::signed(bob()), 0, 5_000));
+        let post_info = EnergyBiddingModule::cancel(RuntimeOrigin::signed(alice()), 1).unwrap();
+        assert_eq!(post_info.actual_weight, Some(Weight::from_parts(100_000_000, 0) + HOOK_WEIGHT));
+
+        // unsold auctions expire without a trade
+        run_to_block(53);
+        assert_eq!(
+            MarketHookCalls::get(),
+            vec![("created", 0), ("created", 1), ("created", 2), ("canceled", 1), ("executed", 0)]
+        );
+    });
+}
+
+#[test]
+fn energy_market_should_work_without_extrinsics() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(2);
+
+        let (auction_id, hook_weight) =
+            EnergyBiddingModule::create_auction(&alice(), 2, 1_000, 5, None).unwrap();
+        assert_eq!(auction_id, 0);
+        assert_eq!(hook_weight, HOOK_WEIGHT);
+        assert_ok!(EnergyBiddingModule::place_bid(&bob(), auction_id, 5_000));
+
+        let auction = EnergyBiddingModule::auction_info(auction_id).unwrap();
+        assert_eq!(auction.seller_id, alice());
+        assert_eq!(auction.end_at, 52);
+        assert_eq!(
+            <EnergyBiddingModule as EnergyMarket<_, _, _, _, _>>::highest_bid(auction_id),
+            Some(Bid { bidder: bob(), bid: 5_000 })
+        );
+        assert_eq!(Balances::reserved_balance(&bob()), 5_000);
+
+        assert_noop!(
+            EnergyBiddingModule::place_bid(&alice(), auction_id, 6_000),
+            Error::<Test>::SellerCannotBid
+        );
+
+        // the listing fee is not kept when the deposit cannot be reserved
+        let dave = AccountId32::from(*b"000000000000000000000DAVE0000000");
+        Balances::make_free_balance_be(&dave, LISTING_FEE + LISTING_DEPOSIT / 2);
+        assert_noop!(
+            EnergyBiddingModule::create_auction(&dave, 2, 1_000, 5, None),
+            Error::<Test>::InsuffficientAttachedDeposit
+        );
+    });
+}
+
+#[test]
+fn market_instances_should_be_isolated() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(2);
+
+        assert_ok!(EnergyBiddingModule::new(
+            RuntimeOrigin::signed(alice()),
+            2,
+            1_000,
+            120,
+            None,
+            None
+        ));
+        assert_ok!(IntradayMarket::new(RuntimeOrigin::signed(alice()), 2, 1_000, 5, None, None));
+        assert_ok!(IntradayMarket::new(RuntimeOrigin::signed(charlie()), 1, 500, 5, None, None));
+
+        // each instance counts its own auction ids
+        assert_eq!(EnergyBiddingModule::auctions_index(), Some(1));
+        assert_eq!(IntradayMarket::auctions_index(), Some(2));
+        assert_eq!(EnergyBiddingModule::auctions(0).unwrap().end_at, 1_202);
+        assert_eq!(IntradayMarket::auctions(0).unwrap().end_at, 52);
+        assert!(EnergyBiddingModule::auctions(1).is_none());
+
+        // periods and tiers follow the configuration of the instance
+        assert_noop!(
+            IntradayMarket::new(RuntimeOrigin::signed(alice()), 2, 1_000, 120, None, None),
+            Error::<Test, Instance2>::InvalidAuctionPeriod
+        );
+        assert_eq!(EnergyBiddingModule::auctions(0).unwrap().auction_category, Tier { level: 1 });
+        assert_eq!(IntradayMarket::auctions(0).unwrap().auction_category, Tier { level: 2 });
+
+        assert_ok!(IntradayMarket::bid(RuntimeOrigin::signed(bob()), 0, 5_000));
+        assert_eq!(EnergyBiddingModule::highest_bid(0), Some(Bid { bidder: alice(), bid: 1_000 }));
+        assert!(EnergyBiddingModule::bids_of(&bob()).is_empty());
+        assert_eq!(IntradayMarket::bids_of(&bob()), vec![(0, Bid { bidder: bob(), bid: 5_000 })]);
+
+        run_to_block(53);
+        assert!(IntradayMarket::settlements(0).is_some());
+        assert!(EnergyBiddingModule::settlements(0).is_none());
+        assert!(EnergyBiddingModule::auctions(0).is_some());
+
+        // fees go to the treasury of the instance that cleared the trade
+        assert_eq!(
+            Balances::free_balance(intraday_treasury()),
+            1 + 2 * LISTING_FEE + Perbill::from_percent(2).mul_floor(5_000)
+        );
+        assert_eq!(Balances::free_balance(treasury()), 1 + LISTING_FEE);
+    });
+}
+
+#[test]
+fn market_status_should_gate_trading() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(2);
+
+        assert_ok!(EnergyBiddingModule::new(
+            RuntimeOrigin::signed(alice()),
+            2,
+            1_000,
+            5,
+            None,
+            None
+        ));
+        assert_ok!(EnergyBiddingModule::bid(RuntimeOrigin::signed(bob()), 0, 5_000));
+
+        assert_noop!(
+            EnergyBiddingModule::set_market_status(
+                RuntimeOrigin::signed(alice()),
+                MarketStatus::Halted,
+                0
+            ),
+            DispatchError::BadOrigin
+        );
+
+        assert_ok!(EnergyBiddingModule::set_market_status(
+            RuntimeOrigin::root(),
+            MarketStatus::BidsOnly,
+            0
+        ));
+        assert_noop!(
+            EnergyBiddingModule::new(RuntimeOrigin::signed(alice()), 2, 1_000, 5, None, None),
+            Error::<Test>::MarketNotOpen
+        );
+        assert_noop!(
+            EnergyBiddingModule::amend(RuntimeOrigin::signed(alice()), 0, None, None, Some(10)),
+            Error::<Test>::MarketNotOpen
+        );
+        assert_ok!(EnergyBiddingModule::bid(RuntimeOrigin::signed(charlie()), 0, 6_000));
+
+        System::set_block_number(10);
+        assert_ok!(EnergyBiddingModule::set_market_status(
+            RuntimeOrigin::root(),
+            MarketStatus::Halted,
+            0
+        ));
+        assert_eq!(EnergyBiddingModule::halted_since(), Some(10));
+        assert_noop!(
+            EnergyBiddingModule::bid(RuntimeOrigin::signed(bob()), 0, 7_000),
+            Error::<Test>::MarketHalted
+        );
+        assert_noop!(
+            EnergyBiddingModule::cancel(RuntimeOrigin::signed(alice()), 0),
+            Error::<Test>::MarketHalted
+        );
+
+        // ended auctions stay queued while trading is halted
+        run_to_block(60);
+        assert!(EnergyBiddingModule::auctions(0).is_some());
+        assert_eq!(EnergyBiddingModule::auction_execution_queue(52, 0), Some(()));
+
+        // resuming scans every listed auction, which the caller has to account for
+        assert_noop!(
+            EnergyBiddingModule::set_market_status(RuntimeOrigin::root(), MarketStatus::Open, 0),
+            Error::<Test>::ListedAuctionsUnderestimated
+        );
+        let post_info =
+            EnergyBiddingModule::set_market_status(RuntimeOrigin::root(), MarketStatus::Open, 10)
+                .unwrap();
+        assert_eq!(post_info.actual_weight, Some(<() as WeightInfo>::set_market_status(1)));
+        System::assert_last_event(Event::MarketStatusChanged { status: MarketStatus::Open }.into());
+        assert_eq!(EnergyBiddingModule::halted_since(), None);
+
+        // the auction gets back the 50 blocks it was halted for
+        assert_eq!(EnergyBiddingModule::auctions(0).unwrap().end_at, 102);
+        assert_eq!(EnergyBiddingModule::auction_execution_queue(52, 0), None);
+        assert_ok!(EnergyBiddingModule::bid(RuntimeOrigin::signed(bob()), 0, 7_000));
+
+        run_to_block(103);
+        assert_eq!(EnergyBiddingModule::settlements(0).map(|s| s.clearing_price), Some(7_000));
+    });
+}
+
+#[test]
+fn circuit_breaker_should_halt_on_price_jump() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(2);
+
+        assert_ok!(EnergyBiddingModule::new(
+            RuntimeOrigin::signed(alice()),
+            2,
+            1_000,
+            5,
+            None,
+            None
+        ));
+        assert_ok!(EnergyBiddingModule::new(
+            RuntimeOrigin::signed(alice()),
+            2,
+            1_000,
+            10,
+            None,
+            None
+        ));
+        assert_ok!(EnergyBiddingModule::new(
+            RuntimeOrigin::signed(alice()),
+            2,
+            1_000,
+            15,
+            None,
+            None
+        ));
+        assert_ok!(EnergyBiddingModule::bid(RuntimeOrigin::signed(bob()), 0, 2_000));
+        assert_ok!(EnergyBiddingModule::bid(RuntimeOrigin::signed(bob()), 1, 5_000));
+        assert_ok!(EnergyBiddingModule::bid(RuntimeOrigin::signed(charlie()), 2, 2_200));
+
+        run_to_block(53);
+        assert_eq!(EnergyBiddingModule::market_status(), MarketStatus::Open);
+
+        // 2_500 per kWh is more than twice the index of 1_000
+        run_to_block(103);
+        assert!(EnergyBiddingModule::settlements(1).is_some());
+        assert_eq!(EnergyBiddingModule::market_status(), MarketStatus::Halted);
+        assert_eq!(EnergyBiddingModule::halted_since(), Some(103));
+        System::assert_has_event(
+            Event::CircuitBreakerTripped {
+                auction_id: 1,
+                tier_level: 1,
+                unit_price: 2_500,
+                reference_price: 1_000,
+            }
+            .into(),
+        );
+
+        run_to_block(160);
+        assert!(EnergyBiddingModule::auctions(2).is_some());
+
+        assert_ok!(EnergyBiddingModule::set_market_status(
+            RuntimeOrigin::root(),
+            MarketStatus::Open,
+            1
+        ));
+        assert_eq!(EnergyBiddingModule::auctions(2).unwrap().end_at, 209);
+    });
+}
+
+#[test]
+fn price_limits_should_bound_prices() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(2);
+
+        assert_noop!(
+            EnergyBiddingModule::set_price_limits(
+                RuntimeOrigin::signed(alice()),
+                1,
+                None,
+                Some(100),
+                Some(1_000)
+            ),
+            DispatchError::BadOrigin
+        );
+        assert_noop!(
+            EnergyBiddingModule::set_price_limits(
+                RuntimeOrigin::root(),
+                1,
+                None,
+                Some(10),
+                Some(1)
+            ),
+            Error::<Test>::InvalidPriceLimits
+        );
+        assert_noop!(
+            EnergyBiddingModule::set_price_limits(RuntimeOrigin::root(), 1, Some(24), None, None),
+            Error::<Test>::InvalidDeliveryPeriod
+        );
+
+        // limits are per kWh, the lots below hold 2 kWh
+        assert_ok!(EnergyBiddingModule::set_price_limits(
+            RuntimeOrigin::root(),
+            1,
+            None,
+            Some(100),
+            Some(1_000)
+        ));
+        System::assert_last_event(
+            Event::PriceLimitsUpdated {
+                tier_level: 1,
+                delivery_period: None,
+                floor: Some(100),
+                cap: Some(1_000),
+            }
+            .into(),
+        );
+
+        assert_noop!(
+            EnergyBiddingModule::new(RuntimeOrigin::signed(alice()), 2, 199, 5, None, None),
+            Error::<Test>::StartingPriceBelowFloor
+        );
+        assert_noop!(
+            EnergyBiddingModule::new(RuntimeOrigin::signed(alice()), 2, 2_001, 5, None, None),
+            Error::<Test>::StartingPriceAboveCap
+        );
+        assert_ok!(EnergyBiddingModule::new(RuntimeOrigin::signed(alice()), 2, 500, 5, None, None));
+        // tier 2 has no limits
+        assert_ok!(EnergyBiddingModule::new(
+            RuntimeOrigin::signed(alice()),
+            5,
+            50_000,
+            5,
+            None,
+            None
+        ));
+
+        assert_noop!(
+            EnergyBiddingModule::bid(RuntimeOrigin::signed(bob()), 0, 2_001),
+            Error::<Test>::BidAboveCap
+        );
+        assert_ok!(EnergyBiddingModule::bid(RuntimeOrigin::signed(bob()), 0, 1_000));
+
+        // the auction ends at block 52, in the first delivery period of the day
+        assert_eq!(EnergyBiddingModule::delivery_period(52), 0);
+        assert_ok!(EnergyBiddingModule::set_price_limits(
+            RuntimeOrigin::root(),
+            1,
+            Some(0),
+            Some(700),
+            None
+        ));
+        assert_eq!(
+            EnergyBiddingModule::price_limit(1, 0),
+            PriceLimit { floor: Some(700), cap: None }
+        );
+        assert_noop!(
+            EnergyBiddingModule::bid(RuntimeOrigin::signed(charlie()), 0, 1_200),
+            Error::<Test>::BidBelowFloor
+        );
+        assert_ok!(EnergyBiddingModule::bid(RuntimeOrigin::signed(charlie()), 0, 3_000));
+
+        // removing the period's limits falls back to the tier's
+        assert_ok!(EnergyBiddingModule::set_price_limits(
+            RuntimeOrigin::root(),
+            1,
+            Some(0),
+            None,
+            None
+        ));
+        assert_eq!(EnergyBiddingModule::period_price_limits(1, 0), None);
+        assert_eq!(
+            EnergyBiddingModule::price_limit(1, 0),
+            PriceLimit { floor: Some(100), cap: Some(1_000) }
+        );
+    });
+}
+
+#[test]
+fn postponed_auction_should_keep_its_delivery_period() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(540);
+
+        assert_ok!(EnergyBiddingModule::new(
+            RuntimeOrigin::signed(alice()),
+            2,
+            1_000,
+            5,
+            None,
+            None
+        ));
+        assert_eq!(EnergyBiddingModule::auctions(0).unwrap().delivery_period, 0);
+        // the next hour is priced well above this auction's bids
+        assert_ok!(EnergyBiddingModule::set_price_limits(
+            RuntimeOrigin::root(),
+            1,
+            Some(1),
+            Some(5_000),
+            None
+        ));
+
+        System::set_block_number(545);
+        assert_ok!(EnergyBiddingModule::set_market_status(
+            RuntimeOrigin::root(),
+            MarketStatus::Halted,
+            0
+        ));
+        System::set_block_number(600);
+        assert_ok!(EnergyBiddingModule::set_market_status(
+            RuntimeOrigin::root(),
+            MarketStatus::Open,
+            1
+        ));
+
+        // the auction now ends in the next hour but still delivers in the one it was listed for
+        let auction = EnergyBiddingModule::auctions(0).unwrap();
+        assert_eq!(auction.end_at, 645);
+        assert_eq!(EnergyBiddingModule::delivery_period(645), 1);
+        assert_eq!(auction.delivery_period, 0);
+        assert_ok!(EnergyBiddingModule::bid(RuntimeOrigin::signed(bob()), 0, 3_000));
+    });
+}
+
+#[test]
+fn bid_limits_and_deposits_should_work() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(2);
+
+        for seller in [alice(), charlie(), alice()] {
+            assert_ok!(IntradayMarket::new(RuntimeOrigin::signed(seller), 2, 1_000, 5, None, None));
+        }
+
+        assert_ok!(IntradayMarket::bid(RuntimeOrigin::signed(bob()), 0, 1_100));
+        assert_eq!(Balances::reserved_balance(bob()), 1_100 + BID_DEPOSIT);
+        assert_noop!(
+            IntradayMarket::bid(RuntimeOrigin::signed(charlie()), 0, 1_100),
+            Error::<Test, Instance2>::BidTooLow
+        );
+
+        assert_ok!(IntradayMarket::bid(RuntimeOrigin::signed(bob()), 0, 1_200));
+        assert_eq!(Balances::reserved_balance(bob()), 1_200 + 2 * BID_DEPOSIT);
+        assert_eq!(IntradayMarket::bid_deposits(0, bob()), Some(2 * BID_DEPOSIT));
+        assert_noop!(
+            IntradayMarket::bid(RuntimeOrigin::signed(bob()), 0, 1_300),
+            Error::<Test, Instance2>::TooManyBidsInAuction
+        );
+
+        assert_ok!(IntradayMarket::bid(RuntimeOrigin::signed(bob()), 1, 1_100));
+        assert_eq!(IntradayMarket::live_bids(bob()), 3);
+        assert_noop!(
+            IntradayMarket::bid(RuntimeOrigin::signed(bob()), 2, 1_100),
+            Error::<Test, Instance2>::TooManyLiveBids
+        );
+
+        // retracting returns the deposit and frees a live bid
+        assert_ok!(IntradayMarket::retract_bid(RuntimeOrigin::signed(bob()), 1));
+        assert_eq!(IntradayMarket::bid_deposits(1, bob()), None);
+        assert_eq!(IntradayMarket::live_bids(bob()), 2);
+        assert_eq!(Balances::reserved_balance(bob()), 1_200 + 2 * BID_DEPOSIT);
+
+        // the deposits are refunded once the auction is executed
+        run_to_block(54);
+        assert!(IntradayMarket::settlements(0).is_some());
+        assert_eq!(IntradayMarket::bid_deposits(0, bob()), None);
+        assert_eq!(IntradayMarket::live_bids(bob()), 0);
+        assert_eq!(Balances::reserved_balance(bob()), 0);
+    });
+}
+
+#[test]
+fn auto_bids_should_raise_past_the_per_auction_bid_limit() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(2);
+
+        assert_ok!(IntradayMarket::new(RuntimeOrigin::signed(alice()), 2, 1_000, 5, None, None));
+        assert_ok!(IntradayMarket::set_auto_bid(RuntimeOrigin::signed(bob()), 0, 10_000, 100));
+
+        let dave = AccountId32::from(*b"000000000000000000000DAVE0000000");
+        let eve = AccountId32::from(*b"000000000000000000000EVE00000000");
+        Balances::make_free_balance_be(&dave, INITIAL_BALANCE);
+        Balances::make_free_balance_be(&eve, INITIAL_BALANCE);
+
+        // the proxy answers more bids than a bidder may place in one auction
+        for (bidder, bid) in [(charlie(), 2_000), (charlie(), 3_000), (dave, 4_000), (eve, 5_000)] {
+            assert_ok!(IntradayMarket::bid(RuntimeOrigin::signed(bidder), 0, bid));
+            assert_eq!(IntradayMarket::highest_bid(0), Some(Bid { bidder: bob(), bid: bid + 100 }));
+        }
+
+        // every raise replaced the proxy's entry instead of adding one
+        let auction = IntradayMarket::auctions(0).unwrap();
+        assert_eq!(auction.bids.iter().filter(|bid| bid.bidder == bob()).count(), 1);
+        assert_eq!(IntradayMarket::live_bids(bob()), 1);
+        assert_eq!(IntradayMarket::bid_deposits(0, bob()), Some(BID_DEPOSIT));
+        assert_eq!(Balances::reserved_balance(bob()), 5_100 + BID_DEPOSIT);
+        assert!(IntradayMarket::auto_bids(0, bob()).is_some());
+    });
+}
+
+#[test]
+fn sponsored_fees_should_respect_quota_and_failures() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(2);
+
+        // The nonce check stands in for the fee charge: it only runs when the call isn't
+        // sponsored.
+        type Sponsor = SponsorMarketFees<Test, (), frame_system::CheckNonce<Test>>;
+        let info = DispatchInfo::default();
+        let is_sponsored = |call: &RuntimeCall, result: DispatchResult| {
+            let nonce = System::account_nonce(bob());
+            let pre = Sponsor::from(frame_system::CheckNonce::from(nonce))
+                .pre_dispatch(&bob(), call, &info, 0)
+                .unwrap();
+            assert_ok!(Sponsor::post_dispatch(Some(pre), &info, &Default::default(), 0, &result));
+            System::account_nonce(bob()) == nonce
+        };
+        let market_call: RuntimeCall = crate::Call::<Test>::cancel { auction_id: 0 }.into();
+        let intraday_call: RuntimeCall =
+            crate::Call::<Test, Instance2>::cancel { auction_id: 0 }.into();
+
+        assert!(!is_sponsored(&market_call, Ok(())));
+
+        assert_noop!(
+            EnergyBiddingModule::register_prosumer(RuntimeOrigin::signed(alice()), bob()),
+            DispatchError::BadOrigin
+        );
+        assert_ok!(EnergyBiddingModule::register_prosumer(RuntimeOrigin::root(), bob()));
+        assert_noop!(
+            EnergyBiddingModule::register_prosumer(RuntimeOrigin::root(), bob()),
+            Error::<Test>::ProsumerAlreadyRegistered
+        );
+
+        // only calls into the instance the prosumer is registered with are sponsored
+        assert!(!is_sponsored(&intraday_call, Ok(())));
+        assert!(is_sponsored(&market_call, Ok(())));
+        System::assert_last_event(Event::FeeSponsored { who: bob(), used: 1 }.into());
+        assert!(is_sponsored(&market_call, Ok(())));
+        assert!(!is_sponsored(&market_call, Ok(())));
+
+        // the quota is renewed every period
+        System::set_block_number(2 + SPONSORSHIP_PERIOD);
+        assert!(is_sponsored(&market_call, Err(DispatchError::Other("failed"))));
+        System::assert_last_event(Event::FeeSponsored { who: bob(), used: 1 }.into());
+        assert_eq!(EnergyBiddingModule::small_prosumers(bob()).unwrap().failures, 1);
+
+        // repeated failures revoke the sponsorship
+        assert!(is_sponsored(&market_call, Err(DispatchError::Other("failed"))));
+        System::assert_last_event(Event::SponsorshipRevoked { who: bob(), failures: 2 }.into());
+        assert!(EnergyBiddingModule::small_prosumers(bob()).is_none());
+        assert!(!is_sponsored(&market_call, Ok(())));
+
+        assert_noop!(
+            EnergyBiddingModule::deregister_prosumer(RuntimeOrigin::root(), bob()),
+            Error::<Test>::ProsumerNotRegistered
+        );
+    });
+}
+
+#[test]
+fn sponsored_validation_should_check_the_quota() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(2);
+
+        type Sponsor = SponsorMarketFees<Test, (), frame_system::CheckNonce<Test>>;
+        let info = DispatchInfo::default();
+        let market_call: RuntimeCall = crate::Call::<Test>::cancel { auction_id: 0 }.into();
+        let validate = || {
+            Sponsor::from(frame_system::CheckNonce::from(0)).validate(
+                &bob(),
+                &market_call,
+                &info,
+                0,
+            )
+        };
+        let charged =
+            || frame_system::CheckNonce::<Test>::from(0).validate(&bob(), &market_call, &info, 0);
+
+        assert_eq!(validate(), charged());
+
+        assert_ok!(EnergyBiddingModule::register_prosumer(RuntimeOrigin::root(), bob()));
+        let sponsored = validate().unwrap();
+        assert_eq!(sponsored.priority, 0);
+        assert_eq!(sponsored.provides, vec![("SponsorMarketFees", (bob(), (2u64, 0u32))).encode()]);
+        assert_ne!(Ok(sponsored), charged());
+
+        // once the quota is used up the call is validated like any other
+        for _ in 0..2 {
+            EnergyBiddingModule::use_sponsorship(&bob());
+        }
+        assert_eq!(validate(), charged());
+    });
+}
+
+#[test]
+fn retracting_the_highest_bid_should_resolve_auto_bids() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(2);
+
+        assert_ok!(EnergyBiddingModule::new(
+            RuntimeOrigin::signed(alice()),
+            2,
+            1_000,
+            5,
+            None,
+            None
+        ));
+        assert_ok!(EnergyBiddingModule::bid(RuntimeOrigin::signed(bob()), 0, 2_000));
+        assert_ok!(EnergyBiddingModule::bid(RuntimeOrigin::signed(charlie()), 0, 3_000));
+
+        // auto bids below the highest bid are normally exhausted, so put one in place directly
+        let dave = AccountId32::from(*b"000000000000000000000DAVE0000000");
+        Balances::make_free_balance_be(&dave, INITIAL_BALANCE);
+        let auto_bid = AutoBid { max_price: 2_500, step: 100, set_at: (2, 0) };
+        crate::AutoBids::<Test>::insert(0, &dave, auto_bid);
+
+        // the restored highest bid is answered right away
+        assert_ok!(EnergyBiddingModule::retract_bid(RuntimeOrigin::signed(charlie()), 0));
+        assert_eq!(
+            EnergyBiddingModule::highest_bid(0),
+            Some(Bid { bidder: dave.clone(), bid: 2_100 })
+        );
+        assert_eq!(Balances::reserved_balance(&dave), 2_100);
+        assert_eq!(Balances::reserved_balance(bob()), 0);
+    });
+}
+
+#[test]
+fn auto_bids_should_outbid_up_to_their_maximum() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(2);
+
+        assert_ok!(EnergyBiddingModule::new(
+            RuntimeOrigin::signed(alice()),
+            2,
+            1_000,
+            5,
+            None,
+            None
+        ));
+        assert_noop!(
+            EnergyBiddingModule::set_auto_bid(RuntimeOrigin::signed(bob()), 0, 5_000, 0),
+            Error::<Test>::InvalidBidStep
+        );
+        assert_noop!(
+            EnergyBiddingModule::set_auto_bid(RuntimeOrigin::signed(alice()), 0, 5_000, 100),
+            Error::<Test>::SellerCannotBid
+        );
+
+        // the auto bid outbids the standing bid right away
+        assert_ok!(EnergyBiddingModule::set_auto_bid(RuntimeOrigin::signed(bob()), 0, 5_000, 100));
+        assert_eq!(EnergyBiddingModule::highest_bid(0), Some(Bid { bidder: bob(), bid: 1_100 }));
+        assert_noop!(
+            EnergyBiddingModule::set_auto_bid(RuntimeOrigin::signed(charlie()), 0, 1_100, 10),
+            Error::<Test>::AutoBidTooLow
+        );
+
+        // and answers manual bids
+        assert_ok!(EnergyBiddingModule::bid(RuntimeOrigin::signed(charlie()), 0, 2_000));
+        assert_eq!(EnergyBiddingModule::highest_bid(0), Some(Bid { bidder: bob(), bid: 2_100 }));
+        assert_eq!(Balances::reserved_balance(bob()), 2_100);
+
+        // the higher maximum wins one step above the other
+        assert_ok!(EnergyBiddingModule::set_auto_bid(
+            RuntimeOrigin::signed(charlie()),
+            0,
+            3_000,
+            50
+        ));
+        assert_eq!(EnergyBiddingModule::highest_bid(0), Some(Bid { bidder: bob(), bid: 3_100 }));
+        System::assert_last_event(
+            Event::AutoBidExhausted { auction_id: 0, bidder: charlie() }.into(),
+        );
+        assert!(EnergyBiddingModule::auto_bids(0, charlie()).is_none());
+
+        // of two equal maximums the earlier wins, at that maximum
+        assert_ok!(EnergyBiddingModule::new(
+            RuntimeOrigin::signed(charlie()),
+            2,
+            1_000,
+            5,
+            None,
+            None
+        ));
+        assert_ok!(EnergyBiddingModule::set_auto_bid(
+            RuntimeOrigin::signed(alice()),
+            1,
+            4_000,
+            100
+        ));
+        System::set_block_number(3);
+        assert_ok!(EnergyBiddingModule::set_auto_bid(RuntimeOrigin::signed(bob()), 1, 4_000, 100));
+        assert_eq!(EnergyBiddingModule::highest_bid(1), Some(Bid { bidder: alice(), bid: 4_000 }));
+        assert!(EnergyBiddingModule::auto_bids(1, bob()).is_none());
+
+        assert_ok!(EnergyBiddingModule::cancel_auto_bid(RuntimeOrigin::signed(bob()), 0));
+        assert_noop!(
+            EnergyBiddingModule::cancel_auto_bid(RuntimeOrigin::signed(bob()), 0),
+            Error::<Test>::AutoBidDoesNotExist
+        );
+
+        // auto bids go away with their auction
+        run_to_block(54);
+        assert!(EnergyBiddingModule::auto_bids(1, alice()).is_none());
+    });
+}
+
+#[test]
+fn batches_should_be_all_or_nothing() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(2);
+
+        let listing = |starting_price, auction_period| ListingSpec {
+            energy_quantity: 2,
+            starting_price,
+            auction_period,
+            min_bidder_reputation: None,
+            buyers: None,
+        };
+
+        assert_noop!(
+            EnergyBiddingModule::new_batch(RuntimeOrigin::signed(alice()), vec![]),
+            Error::<Test>::EmptyBatch
+        );
+        assert_noop!(
+            EnergyBiddingModule::new_batch(
+                RuntimeOrigin::signed(alice()),
+                vec![listing(1_000, 5); 9]
+            ),
+            Error::<Test>::BatchTooLarge
+        );
+        assert_noop!(
+            EnergyBiddingModule::new_batch(
+                RuntimeOrigin::signed(alice()),
+                vec![listing(1_000, 5), listing(1_000, 2_000)]
+            ),
+            Error::<Test>::InvalidAuctionPeriod
+        );
+
+        let listings = vec![listing(1_000, 5), listing(2_000, 5), listing(3_000, 10)];
+        let call = crate::Call::<Test>::new_batch { listings: listings.clone() };
+        assert_eq!(call.get_dispatch_info().weight, <() as WeightInfo>::new_batch(3));
+        assert_ok!(EnergyBiddingModule::new_batch(RuntimeOrigin::signed(alice()), listings));
+        System::assert_last_event(
+            Event::ListingBatchCreated { seller_id: alice(), auction_ids: vec![0, 1, 2] }.into(),
+        );
+        assert_eq!(EnergyBiddingModule::auctions(2).unwrap().end_at, 102);
+
+        assert_noop!(
+            EnergyBiddingModule::bid_batch(
+                RuntimeOrigin::signed(bob()),
+                vec![(2, 4_000), (0, 500)]
+            ),
+            Error::<Test>::BidTooLow
+        );
+        assert_ok!(EnergyBiddingModule::bid_batch(
+            RuntimeOrigin::signed(bob()),
+            vec![(0, 1_500), (1, 2_500)]
+        ));
+        System::assert_last_event(
+            Event::BidBatchPlaced {
+                bidder: bob(),
+                highest_bids: vec![
+                    (0, Bid { bidder: bob(), bid: 1_500 }),
+                    (1, Bid { bidder: bob(), bid: 2_500 }),
+                ],
+            }
+            .into(),
+        );
+        assert_eq!(Balances::reserved_balance(bob()), 4_000);
+    });
+}
+
+#[test]
+fn schedules_should_list_until_deposit_runs_out() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(2);
+
+        let listing = |auction_period| ListingSpec {
+            energy_quantity: 2,
+            starting_price: 1_000,
+            auction_period,
+            min_bidder_reputation: None,
+            buyers: None,
+        };
+        let create = |who, listing, recurrence, deposit| {
+            EnergyBiddingModule::create_schedule(
+                RuntimeOrigin::signed(who),
+                listing,
+                recurrence,
+                deposit,
+            )
+        };
+
+        assert_noop!(
+            create(alice(), listing(5), Recurrence::Every(0), 100),
+            Error::<Test>::InvalidRecurrence
+        );
+        assert_noop!(
+            create(alice(), listing(5), Recurrence::DailyAt(1_440), 100),
+            Error::<Test>::InvalidRecurrence
+        );
+        assert_noop!(
+            create(alice(), listing(2_000), Recurrence::Every(10), 100),
+            Error::<Test>::InvalidAuctionPeriod
+        );
+        assert_noop!(
+            create(alice(), listing(5), Recurrence::Every(10), LISTING_FEE - 1),
+            Error::<Test>::ScheduleDepositTooLow
+        );
+
+        assert_ok!(create(alice(), listing(5), Recurrence::Every(10), 2 * LISTING_FEE + 5));
+        System::assert_last_event(
+            Event::ScheduleCreated { schedule_id: 0, seller_id: alice(), next_run: 12 }.into(),
+        );
+        assert_eq!(Balances::reserved_balance(alice()), 2 * LISTING_FEE + 5);
+
+        // the weight of the creation hook is part of the block's initialization
+        run_to_block(12);
+        System::set_block_number(13);
+        assert_eq!(
+            EnergyBiddingModule::on_initialize(13),
+            <() as WeightInfo>::on_initialize_base() +
+                <() as WeightInfo>::scan_queue() * 4 +
+                <() as WeightInfo>::run_schedule() +
+                HOOK_WEIGHT
+        );
+        System::assert_last_event(Event::ScheduleRun { schedule_id: 0, auction_id: 0 }.into());
+        assert_eq!(EnergyBiddingModule::auctions(0).unwrap().start_at, 13);
+        assert_eq!(EnergyBiddingModule::schedules(0).unwrap().next_run, 23);
+        assert_eq!(Balances::reserved_balance(alice()), LISTING_FEE + 5 + LISTING_DEPOSIT);
+
+        // the second run uses up the deposit and ends the schedule
+        run_to_block(23);
+        assert!(EnergyBiddingModule::auctions(1).is_none());
+        run_to_block(24);
+        assert!(EnergyBiddingModule::auctions(1).is_some());
+        System::assert_last_event(Event::ScheduleExhausted { schedule_id: 0 }.into());
+        assert!(EnergyBiddingModule::schedules(0).is_none());
+        assert_eq!(Balances::reserved_balance(alice()), 2 * LISTING_DEPOSIT);
+
+        // daily schedules run at their minute of the day
+        assert_ok!(create(bob(), listing(5), Recurrence::DailyAt(1), 100));
+        assert_eq!(EnergyBiddingModule::schedules(1).unwrap().next_run, 14_410);
+        assert!(EnergyBiddingModule::schedule_queue(14_410, 1).is_some());
+
+        assert_noop!(
+            EnergyBiddingModule::cancel_schedule(RuntimeOrigin::signed(alice()), 1),
+            Error::<Test>::NotSeller
+        );
+        assert_ok!(EnergyBiddingModule::cancel_schedule(RuntimeOrigin::signed(bob()), 1));
+        assert!(EnergyBiddingModule::schedule_queue(14_410, 1).is_none());
+        assert_eq!(Balances::reserved_balance(bob()), 0);
+        assert_noop!(
+            EnergyBiddingModule::cancel_schedule(RuntimeOrigin::signed(bob()), 1),
+            Error::<Test>::ScheduleDoesNotExist
+        );
+    });
+}
+
+#[test]
+fn private_auctions_should_only_accept_listed_buyers() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(2);
+
+        let group = b"pre-qualified".to_vec();
+        let new_auction = |buyers| {
+            EnergyBiddingModule::new(RuntimeOrigin::signed(alice()), 2, 1_000, 5, None, buyers)
+        };
+
+        assert_noop!(
+            EnergyBiddingModule::set_buyer_group(
+                RuntimeOrigin::signed(alice()),
+                vec![0; 33],
+                vec![bob()]
+            ),
+            Error::<Test>::GroupNameTooLong
+        );
+        assert_ok!(EnergyBiddingModule::set_buyer_group(
+            RuntimeOrigin::signed(alice()),
+            group.clone(),
+            vec![bob()]
+        ));
+        System::assert_last_event(
+            Event::BuyerGroupUpdated { owner: alice(), name: group.clone(), members: 1 }.into(),
+        );
+
+        assert_noop!(
+            new_auction(Some(BuyerAccess::AllowList(vec![]))),
+            Error::<Test>::EmptyBuyerList
+        );
+        // groups are looked up among the seller's own
+        assert_noop!(
+            EnergyBiddingModule::new(
+                RuntimeOrigin::signed(bob()),
+                2,
+                1_000,
+                5,
+                None,
+                Some(BuyerAccess::Group(group.clone()))
+            ),
+            Error::<Test>::BuyerGroupDoesNotExist
+        );
+
+        assert_ok!(new_auction(None));
+        assert_ok!(new_auction(Some(BuyerAccess::AllowList(vec![charlie()]))));
+        assert_ok!(new_auction(Some(BuyerAccess::Group(group.clone()))));
+
+        let listed: Vec<u64> = EnergyBiddingModule::open_auctions(None, 0, 10)
+            .into_iter()
+            .map(|auction| auction.auction_id)
+            .collect();
+        assert_eq!(listed, vec![0]);
+
+        assert_noop!(
+            EnergyBiddingModule::bid(RuntimeOrigin::signed(bob()), 1, 2_000),
+            Error::<Test>::BuyerNotAllowed
+        );
+        assert_noop!(
+            EnergyBiddingModule::set_auto_bid(RuntimeOrigin::signed(bob()), 1, 5_000, 100),
+            Error::<Test>::BuyerNotAllowed
+        );
+        assert_ok!(EnergyBiddingModule::bid(RuntimeOrigin::signed(charlie()), 1, 2_000));
+
+        assert_ok!(EnergyBiddingModule::bid(RuntimeOrigin::signed(bob()), 2, 2_000));
+        assert_noop!(
+            EnergyBiddingModule::bid(RuntimeOrigin::signed(charlie()), 2, 3_000),
+            Error::<Test>::BuyerNotAllowed
+        );
+
+        // auctions restricted to a group follow its current members
+        assert_ok!(EnergyBiddingModule::set_buyer_group(
+            RuntimeOrigin::signed(alice()),
+            group.clone(),
+            vec![bob(), charlie()]
+        ));
+        assert_ok!(EnergyBiddingModule::bid(RuntimeOrigin::signed(charlie()), 2, 3_000));
+
+        assert_ok!(EnergyBiddingModule::set_buyer_group(
+            RuntimeOrigin::signed(alice()),
+            group.clone(),
+            vec![]
+        ));
+        System::assert_last_event(Event::BuyerGroupRemoved { owner: alice(), name: group }.into());
+        assert_noop!(
+            EnergyBiddingModule::bid(RuntimeOrigin::signed(bob()), 2, 4_000),
+            Error::<Test>::BuyerNotAllowed
+        );
+
+        run_to_block(54);
+        assert!(EnergyBiddingModule::auction_access(1).is_none());
+    });
+}
+
+#[test]
+fn private_listings_should_be_batched_and_scheduled() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(2);
+
+        let group = b"pre-qualified".to_vec();
+        let listing = |buyers| ListingSpec {
+            energy_quantity: 2,
+            starting_price: 1_000,
+            auction_period: 5,
+            min_bidder_reputation: None,
+            buyers,
+        };
+
+        // duplicates are dropped before the allow-list is checked against its limit
+        assert_noop!(
+            EnergyBiddingModule::new_batch(
+                RuntimeOrigin::signed(alice()),
+                vec![listing(Some(BuyerAccess::AllowList(vec![])))]
+            ),
+            Error::<Test>::EmptyBuyerList
+        );
+        let mut buyers = vec![charlie(); 5];
+        buyers.push(bob());
+        assert_ok!(EnergyBiddingModule::new_batch(
+            RuntimeOrigin::signed(alice()),
+            vec![listing(None), listing(Some(BuyerAccess::AllowList(buyers)))]
+        ));
+        assert_eq!(
+            EnergyBiddingModule::auction_access(1),
+            Some(BuyerAccess::AllowList(vec![bob(), charlie()]))
+        );
+        let listed: Vec<u64> = EnergyBiddingModule::open_auctions(None, 0, 10)
+            .into_iter()
+            .map(|auction| auction.auction_id)
+            .collect();
+        assert_eq!(listed, vec![0]);
+
+        assert_ok!(EnergyBiddingModule::set_buyer_group(
+            RuntimeOrigin::signed(alice()),
+            group.clone(),
+            vec![bob(); 5]
+        ));
+        System::assert_last_event(
+            Event::BuyerGroupUpdated { owner: alice(), name: group.clone(), members: 1 }.into(),
+        );
+
+        assert_noop!(
+            EnergyBiddingModule::create_schedule(
+                RuntimeOrigin::signed(alice()),
+                listing(Some(BuyerAccess::Group(b"unknown".to_vec()))),
+                Recurrence::Every(10),
+                100
+            ),
+            Error::<Test>::BuyerGroupDoesNotExist
+        );
+        assert_ok!(EnergyBiddingModule::create_schedule(
+            RuntimeOrigin::signed(alice()),
+            listing(Some(BuyerAccess::Group(group.clone()))),
+            Recurrence::Every(10),
+            100
+        ));
+
+        run_to_block(13);
+        System::assert_last_event(Event::ScheduleRun { schedule_id: 0, auction_id: 2 }.into());
+        assert_eq!(EnergyBiddingModule::auction_access(2), Some(BuyerAccess::Group(group)));
+        assert_noop!(
+            EnergyBiddingModule::bid(RuntimeOrigin::signed(charlie()), 2, 2_000),
+            Error::<Test>::BuyerNotAllowed
+        );
+    });
+}
@@ -0,0 +1,150 @@
+//! Interfaces through which other pallets use the market.
+
+use crate::{AuctionData, Bid, PriceIndexValue, Tier};
+use frame_support::{
+    dispatch::{DispatchError, DispatchResult},
+    weights::Weight,
+};
+
+/// An auction as returned by [`EnergyMarket::auction_info`].
+pub type MarketAuction<AccountId, AuctionId, Quantity, Price, BlockNumber> =
+    AuctionData<AccountId, AuctionId, Bid<AccountId, Price>, BlockNumber, Quantity, Tier>;
+
+/// Listing and bidding on behalf of an account, for runtime logic such as a treasury selling
+/// excess energy. Each call either fully applies or leaves no trace.
+pub trait EnergyMarket<AccountId, AuctionId, Quantity, Price, BlockNumber> {
+    /// Lists `quantity` for sale by `seller`, open for `auction_period` minutes. The listing fee
+    /// and deposit are taken from `seller`. Returns the id of the new auction and the weight of
+    /// the `OnAuctionCreated` handler, which the caller has to account for.
+    fn create_auction(
+        seller: &AccountId,
+        quantity: Quantity,
+        starting_price: Price,
+        auction_period: u16,
+        min_bidder_reputation: Option<u32>,
+    ) -> Result<(AuctionId, Weight), DispatchError>;
+
+    /// Bids `bid` on an auction, reserving it from `bidder`. The bid must beat the current
+    /// highest bid, which is released to its bidder.
+    fn place_bid(bidder: &AccountId, auction_id: AuctionId, bid: Price) -> DispatchResult;
+
+    /// The auction with the given id, if it is still listed.
+    fn auction_info(
+        auction_id: AuctionId,
+    ) -> Option<MarketAuction<AccountId, AuctionId, Quantity, Price, BlockNumber>>;
+
+    /// The current highest bid of an auction. The seller's starting bid until someone bids.
+    fn highest_bid(auction_id: AuctionId) -> Option<Bid<AccountId, Price>>;
+}
+
+/// Reference prices per tier, derived from the clearing prices of the market itself.
+pub trait PriceIndex<Price, BlockNumber> {
+    /// The tier's index as computed at its latest trade.
+    fn current_index(tier_level: u32) -> Option<PriceIndexValue<Price, BlockNumber>>;
+
+    /// Time-weighted average unit price of the tier over the last `window` blocks.
+    fn time_weighted_price(tier_level: u32, window: BlockNumber) -> Option<Price>;
+
+    /// Volume-weighted average unit price of the tier over the last `window` blocks.
+    fn volume_weighted_price(tier_level: u32, window: BlockNumber) -> Option<Price>;
+}
+
+impl<Price, BlockNumber> PriceIndex<Price, BlockNumber> for () {
+    fn current_index(_: u32) -> Option<PriceIndexValue<Price, BlockNumber>> {
+        None
+    }
+
+    fn time_weighted_price(_: u32, _: BlockNumber) -> Option<Price> {
+        None
+    }
+
+    fn volume_weighted_price(_: u32, _: BlockNumber) -> Option<Price> {
+        None
+    }
+}
+
+/// Handler for trades cleared by the market. Returns the weight it consumed.
+pub trait OnAuctionExecuted<AccountId, AuctionId, Quantity, Price> {
+    fn on_auction_executed(
+        auction_id: AuctionId,
+        seller: &AccountId,
+        buyer: &AccountId,
+        quantity: Quantity,
+        clearing_price: Price,
+    ) -> Weight;
+}
+
+#[impl_trait_for_tuples::impl_for_tuples(30)]
+impl<AccountId, AuctionId: Copy, Quantity: Copy, Price: Copy>
+    OnAuctionExecuted<AccountId, AuctionId, Quantity, Price> for Tuple
+{
+    fn on_auction_executed(
+        auction_id: AuctionId,
+        seller: &AccountId,
+        buyer: &AccountId,
+        quantity: Quantity,
+        clearing_price: Price,
+    ) -> Weight {
+        let mut weight = Weight::zero();
+        for_tuples!( #( weight = weight.saturating_add(
+            Tuple::on_auction_executed(auction_id, seller, buyer, quantity, clearing_price)
+        ); )* );
+        weight
+    }
+}
+
+/// Handler for newly listed auctions. Returns the weight it consumed.
+pub trait OnAuctionCreated<AccountId, AuctionId, Quantity, Price> {
+    fn on_auction_created(
+        auction_id: AuctionId,
+        seller: &AccountId,
+        quantity: Quantity,
+        starting_price: Price,
+    ) -> Weight;
+}
+
+#[impl_trait_for_tuples::impl_for_tuples(30)]
+impl<AccountId, AuctionId: Copy, Quantity: Copy, Price: Copy>
+    OnAuctionCreated<AccountId, AuctionId, Quantity, Price> for Tuple
+{
+    fn on_auction_created(
+        auction_id: AuctionId,
+        seller: &AccountId,
+        quantity: Quantity,
+        starting_price: Price,
+    ) -> Weight {
+        let mut weight = Weight::zero();
+        for_tuples!( #( weight = weight.saturating_add(
+            Tuple::on_auction_created(auction_id, seller, quantity, starting_price)
+        ); )* );
+        weight
+    }
+}
+
+/// Handler for auctions cancelled by their seller. Returns the weight it consumed.
+pub trait OnAuctionCanceled<AccountId, AuctionId, Quantity, Price> {
+    fn on_auction_canceled(
+        auction_id: AuctionId,
+        seller: &AccountId,
+        quantity: Quantity,
+        starting_price: Price,
+    ) -> Weight;
+}
+
+#[impl_trait_for_tuples::impl_for_tuples(30)]
+impl<AccountId, AuctionId: Copy, Quantity: Copy, Price: Copy>
+    OnAuctionCanceled<AccountId, AuctionId, Quantity, Price> for Tuple
+{
+    fn on_auction_canceled(
+        auction_id: AuctionId,
+        seller: &AccountId,
+        quantity: Quantity,
+        starting_price: Price,
+    ) -> Weight {
+        let mut weight = Weight::zero();
+        for_tuples!( #( weight = weight.saturating_add(
+            Tuple::on_auction_canceled(auction_id, seller, quantity, starting_price)
+        ); )* );
+        weight
+    }
+}
@@ -1,29 +1,13 @@
 
-//! Autogenerated weights for pallet_template
+//! Weights for energy_bidding
 //!
-//! THIS FILE WAS AUTO-GENERATED USING THE SUBSTRATE BENCHMARK CLI VERSION 4.0.0-dev
-//! DATE: 2023-04-06, STEPS: `50`, REPEAT: `20`, LOW RANGE: `[]`, HIGH RANGE: `[]`
-//! WORST CASE MAP SIZE: `1000000`
-//! HOSTNAME: `Alexs-MacBook-Pro-2.local`, CPU: `<UNKNOWN>`
-//! EXECUTION: Some(Wasm), WASM-EXECUTION: Compiled, CHAIN: Some("dev"), DB CACHE: 1024
-
-// Executed Command:
-// ../../target/release/node-template
-// benchmark
-// pallet
-// --chain
-// dev
-// --pallet
-// pallet_template
-// --extrinsic
-// *
-// --steps=50
-// --repeat=20
-// --wasm-execution=compiled
-// --output
-// pallets/template/src/weights.rs
-// --template
-// ../../.maintain/frame-weight-template.hbs
+//! THESE WEIGHTS ARE PLACEHOLDERS, NOT BENCHMARK RESULTS. They are hand-written from the storage
+//! accesses listed above each function, with guessed execution times and proof sizes. The pallet
+//! has no working benchmarks yet (`benchmarking.rs` is not compiled in), so they must be replaced
+//! with the output of the benchmark CLI before the runtime goes to production.
+//!
+//! Both market instances share these weights. The storage lines name the pallet's items
+//! without an instance prefix; `System Account` is the balances of the accounts involved.
 
 #![cfg_attr(rustfmt, rustfmt_skip)]
 #![allow(unused_parens)]
@@ -32,59 +16,331 @@
 use frame_support::{traits::Get, weights::{Weight, constants::RocksDbWeight}};
 use core::marker::PhantomData;
 
-/// Weight functions needed for pallet_template.
+/// Weight functions needed for energy_bidding.
 pub trait WeightInfo {
-	fn do_something() -> Weight;
-	fn cause_error() -> Weight;
+	fn on_initialize_base() -> Weight;
+	fn scan_queue() -> Weight;
+	fn expire_auction(b: u32, a: u32) -> Weight;
+	fn release_escrow() -> Weight;
+	fn prune_settlement() -> Weight;
+	fn run_schedule() -> Weight;
+	fn new_batch(n: u32) -> Weight;
+	fn bid_batch(n: u32) -> Weight;
+	fn set_market_status(n: u32) -> Weight;
+	fn resolve_auto_bids(n: u32) -> Weight;
 }
 
-/// Weights for pallet_template using the Substrate node and recommended hardware.
+/// Placeholder weights for energy_bidding, with database accesses priced by the runtime.
 pub struct SubstrateWeight<T>(PhantomData<T>);
 impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
-	/// Storage: TemplateModule Something (r:0 w:1)
-	/// Proof: TemplateModule Something (max_values: Some(1), max_size: Some(4), added: 499, mode: MaxEncodedLen)
-	fn do_something() -> Weight {
-		// Proof Size summary in bytes:
-		//  Measured:  `0`
-		//  Estimated: `0`
-		// Minimum execution time: 8_000_000 picoseconds.
-		Weight::from_parts(9_000_000, 0)
+	/// Storage: MarketState (r:1 w:0)
+	/// Storage: ExpiryCursor (r:1 w:1)
+	fn on_initialize_base() -> Weight {
+		Weight::from_parts(3_000_000, 1_489)
+			.saturating_add(T::DbWeight::get().reads(2_u64))
 			.saturating_add(T::DbWeight::get().writes(1_u64))
 	}
-	/// Storage: TemplateModule Something (r:1 w:1)
-	/// Proof: TemplateModule Something (max_values: Some(1), max_size: Some(4), added: 499, mode: MaxEncodedLen)
-	fn cause_error() -> Weight {
-		// Proof Size summary in bytes:
-		//  Measured:  `32`
-		//  Estimated: `1489`
-		// Minimum execution time: 6_000_000 picoseconds.
-		Weight::from_parts(6_000_000, 1489)
+	/// Storage: AuctionsExecutionQueue (r:1 w:0)
+	fn scan_queue() -> Weight {
+		Weight::from_parts(2_000_000, 3_513)
 			.saturating_add(T::DbWeight::get().reads(1_u64))
-			.saturating_add(T::DbWeight::get().writes(1_u64))
+	}
+	/// Storage: AuctionsExecutionQueue (r:0 w:1)
+	/// Storage: Auctions (r:1 w:1)
+	/// Storage: ListingDeposits (r:1 w:1)
+	/// Storage: RelistRounds (r:1 w:1)
+	/// Storage: SellerAuctions (r:0 w:1)
+	/// Storage: BuyerAuctions (r:0 w:b)
+	/// Storage: TierTradeFee (r:1 w:0)
+	/// Storage: Reputations (r:2 w:2)
+	/// Storage: Escrows (r:0 w:1)
+	/// Storage: EscrowReleaseQueue (r:0 w:1)
+	/// Storage: Settlements (r:0 w:1)
+	/// Storage: SettlementPruningQueue (r:0 w:1)
+	/// Storage: PriceObservations (r:1 w:1)
+	/// Storage: PriceObservationCursor (r:1 w:1)
+	/// Storage: PriceIndices (r:1 w:1)
+	/// Storage: MarketState (r:1 w:0)
+	/// Storage: BidDeposits (r:b w:b)
+	/// Storage: LiveBids (r:b w:b)
+	/// Storage: AutoBids (r:0 w:a)
+	/// Storage: System Account (r:3+b w:3+b)
+	/// The range of component `b` is `[0, MaxBidsPerAuction * bidders]`.
+	/// The range of component `a` is `[0, MaxAutoBids]`.
+	fn expire_auction(b: u32, a: u32) -> Weight {
+		Weight::from_parts(60_000_000, 14_000)
+			.saturating_add(Weight::from_parts(6_000_000, 2_603).saturating_mul(b.into()))
+			.saturating_add(Weight::from_parts(1_500_000, 0).saturating_mul(a.into()))
+			.saturating_add(T::DbWeight::get().reads(14_u64))
+			.saturating_add(T::DbWeight::get().reads((3_u64).saturating_mul(b.into())))
+			.saturating_add(T::DbWeight::get().writes(18_u64))
+			.saturating_add(T::DbWeight::get().writes((4_u64).saturating_mul(b.into())))
+			.saturating_add(T::DbWeight::get().writes((1_u64).saturating_mul(a.into())))
+	}
+	/// Storage: Escrows (r:1 w:1)
+	/// Storage: EscrowReleaseQueue (r:0 w:1)
+	/// Storage: System Account (r:1 w:1)
+	fn release_escrow() -> Weight {
+		Weight::from_parts(15_000_000, 3_593)
+			.saturating_add(T::DbWeight::get().reads(2_u64))
+			.saturating_add(T::DbWeight::get().writes(3_u64))
+	}
+	/// Storage: Settlements (r:0 w:1)
+	/// Storage: SettlementPruningQueue (r:0 w:1)
+	fn prune_settlement() -> Weight {
+		Weight::from_parts(5_000_000, 0)
+			.saturating_add(T::DbWeight::get().writes(2_u64))
+	}
+	/// Storage: Schedules (r:1 w:1)
+	/// Storage: ScheduleQueue (r:0 w:2)
+	/// Storage: MarketState (r:1 w:0)
+	/// Storage: AuctionIndex (r:1 w:1)
+	/// Storage: Auctions (r:0 w:1)
+	/// Storage: AuctionsExecutionQueue (r:0 w:1)
+	/// Storage: SellerAuctions (r:0 w:1)
+	/// Storage: ListingDeposits (r:0 w:1)
+	/// Storage: AuctionHistoryCount (r:1 w:1)
+	/// Storage: AuctionHistory (r:0 w:1)
+	/// Storage: Reputations (r:1 w:0)
+	/// Storage: TierPriceLimits (r:1 w:0)
+	/// Storage: PeriodPriceLimits (r:1 w:0)
+	/// Storage: System Account (r:2 w:2)
+	fn run_schedule() -> Weight {
+		Weight::from_parts(95_000_000, 6_196)
+			.saturating_add(T::DbWeight::get().reads(10_u64))
+			.saturating_add(T::DbWeight::get().writes(13_u64))
+	}
+	/// Storage: MarketState (r:1 w:0)
+	/// Storage: AuctionIndex (r:n w:n)
+	/// Storage: Auctions (r:0 w:n)
+	/// Storage: AuctionsExecutionQueue (r:0 w:n)
+	/// Storage: SellerAuctions (r:0 w:n)
+	/// Storage: ListingDeposits (r:0 w:n)
+	/// Storage: AuctionHistoryCount (r:n w:n)
+	/// Storage: AuctionHistory (r:0 w:n)
+	/// Storage: Reputations (r:n w:0)
+	/// Storage: TierPriceLimits (r:n w:0)
+	/// Storage: PeriodPriceLimits (r:n w:0)
+	/// Storage: System Account (r:2 w:2)
+	/// The range of component `n` is `[1, MaxBatchSize]`.
+	fn new_batch(n: u32) -> Weight {
+		Weight::from_parts(12_000_000, 6_196)
+			.saturating_add(Weight::from_parts(85_000_000, 3_513).saturating_mul(n.into()))
+			.saturating_add(T::DbWeight::get().reads(3_u64))
+			.saturating_add(T::DbWeight::get().reads((5_u64).saturating_mul(n.into())))
+			.saturating_add(T::DbWeight::get().writes(2_u64))
+			.saturating_add(T::DbWeight::get().writes((8_u64).saturating_mul(n.into())))
+	}
+	/// Storage: MarketState (r:n w:0)
+	/// Storage: Auctions (r:n w:n)
+	/// Storage: MinSellerReputation (r:1 w:0)
+	/// Storage: Reputations (r:n w:0)
+	/// Storage: TierPriceLimits (r:n w:0)
+	/// Storage: PeriodPriceLimits (r:n w:0)
+	/// Storage: LiveBids (r:1 w:n)
+	/// Storage: BidDeposits (r:n w:n)
+	/// Storage: BuyerAuctions (r:n w:n)
+	/// Storage: AuctionHistoryCount (r:n w:n)
+	/// Storage: AuctionHistory (r:0 w:n)
+	/// Storage: AutoBids (r:n w:0)
+	/// Storage: System Account (r:2n w:2n)
+	/// The range of component `n` is `[1, MaxBatchSize]`.
+	fn bid_batch(n: u32) -> Weight {
+		Weight::from_parts(10_000_000, 3_593)
+			.saturating_add(Weight::from_parts(90_000_000, 7_186).saturating_mul(n.into()))
+			.saturating_add(T::DbWeight::get().reads(2_u64))
+			.saturating_add(T::DbWeight::get().reads((11_u64).saturating_mul(n.into())))
+			.saturating_add(T::DbWeight::get().writes((9_u64).saturating_mul(n.into())))
+	}
+	/// Storage: MarketState (r:1 w:1)
+	/// Storage: HaltedSince (r:1 w:1)
+	/// Storage: Auctions (r:n w:n)
+	/// Storage: AuctionsExecutionQueue (r:0 w:2n)
+	/// The range of component `n` is `[0, listed auctions]`.
+	fn set_market_status(n: u32) -> Weight {
+		Weight::from_parts(8_000_000, 1_489)
+			.saturating_add(Weight::from_parts(7_000_000, 3_593).saturating_mul(n.into()))
+			.saturating_add(T::DbWeight::get().reads(2_u64))
+			.saturating_add(T::DbWeight::get().reads((1_u64).saturating_mul(n.into())))
+			.saturating_add(T::DbWeight::get().writes(2_u64))
+			.saturating_add(T::DbWeight::get().writes((3_u64).saturating_mul(n.into())))
+	}
+	/// Storage: Auctions (r:n w:n)
+	/// Storage: AutoBids (r:n^2 w:n)
+	/// Storage: MarketState (r:n w:0)
+	/// Storage: Reputations (r:n w:0)
+	/// Storage: TierPriceLimits (r:n w:0)
+	/// Storage: PeriodPriceLimits (r:n w:0)
+	/// Storage: LiveBids (r:n w:n)
+	/// Storage: BidDeposits (r:n w:n)
+	/// Storage: BuyerAuctions (r:n w:n)
+	/// Storage: AuctionHistoryCount (r:n w:n)
+	/// Storage: AuctionHistory (r:0 w:n)
+	/// Storage: System Account (r:2n w:2n)
+	/// The range of component `n` is `[0, MaxAutoBids]`. Every auto bid may be outbid or fail
+	/// once, each time reading all of them again.
+	fn resolve_auto_bids(n: u32) -> Weight {
+		Weight::from_parts(2_000_000, 1_489)
+			.saturating_add(Weight::from_parts(90_000_000, 7_186).saturating_mul(n.into()))
+			.saturating_add(T::DbWeight::get().reads(1_u64))
+			.saturating_add(T::DbWeight::get().reads((u64::from(n) + 11).saturating_mul(n.into())))
+			.saturating_add(T::DbWeight::get().writes((10_u64).saturating_mul(n.into())))
 	}
 }
 
 // For backwards compatibility and tests
 impl WeightInfo for () {
-	/// Storage: TemplateModule Something (r:0 w:1)
-	/// Proof: TemplateModule Something (max_values: Some(1), max_size: Some(4), added: 499, mode: MaxEncodedLen)
-	fn do_something() -> Weight {
-		// Proof Size summary in bytes:
-		//  Measured:  `0`
-		//  Estimated: `0`
-		// Minimum execution time: 8_000_000 picoseconds.
-		Weight::from_parts(9_000_000, 0)
+	/// Storage: MarketState (r:1 w:0)
+	/// Storage: ExpiryCursor (r:1 w:1)
+	fn on_initialize_base() -> Weight {
+		Weight::from_parts(3_000_000, 1_489)
+			.saturating_add(RocksDbWeight::get().reads(2_u64))
 			.saturating_add(RocksDbWeight::get().writes(1_u64))
 	}
-	/// Storage: TemplateModule Something (r:1 w:1)
-	/// Proof: TemplateModule Something (max_values: Some(1), max_size: Some(4), added: 499, mode: MaxEncodedLen)
-	fn cause_error() -> Weight {
-		// Proof Size summary in bytes:
-		//  Measured:  `32`
-		//  Estimated: `1489`
-		// Minimum execution time: 6_000_000 picoseconds.
-		Weight::from_parts(6_000_000, 1489)
+	/// Storage: AuctionsExecutionQueue (r:1 w:0)
+	fn scan_queue() -> Weight {
+		Weight::from_parts(2_000_000, 3_513)
 			.saturating_add(RocksDbWeight::get().reads(1_u64))
-			.saturating_add(RocksDbWeight::get().writes(1_u64))
+	}
+	/// Storage: AuctionsExecutionQueue (r:0 w:1)
+	/// Storage: Auctions (r:1 w:1)
+	/// Storage: ListingDeposits (r:1 w:1)
+	/// Storage: RelistRounds (r:1 w:1)
+	/// Storage: SellerAuctions (r:0 w:1)
+	/// Storage: BuyerAuctions (r:0 w:b)
+	/// Storage: TierTradeFee (r:1 w:0)
+	/// Storage: Reputations (r:2 w:2)
+	/// Storage: Escrows (r:0 w:1)
+	/// Storage: EscrowReleaseQueue (r:0 w:1)
+	/// Storage: Settlements (r:0 w:1)
+	/// Storage: SettlementPruningQueue (r:0 w:1)
+	/// Storage: PriceObservations (r:1 w:1)
+	/// Storage: PriceObservationCursor (r:1 w:1)
+	/// Storage: PriceIndices (r:1 w:1)
+	/// Storage: MarketState (r:1 w:0)
+	/// Storage: BidDeposits (r:b w:b)
+	/// Storage: LiveBids (r:b w:b)
+	/// Storage: AutoBids (r:0 w:a)
+	/// Storage: System Account (r:3+b w:3+b)
+	/// The range of component `b` is `[0, MaxBidsPerAuction * bidders]`.
+	/// The range of component `a` is `[0, MaxAutoBids]`.
+	fn expire_auction(b: u32, a: u32) -> Weight {
+		Weight::from_parts(60_000_000, 14_000)
+			.saturating_add(Weight::from_parts(6_000_000, 2_603).saturating_mul(b.into()))
+			.saturating_add(Weight::from_parts(1_500_000, 0).saturating_mul(a.into()))
+			.saturating_add(RocksDbWeight::get().reads(14_u64))
+			.saturating_add(RocksDbWeight::get().reads((3_u64).saturating_mul(b.into())))
+			.saturating_add(RocksDbWeight::get().writes(18_u64))
+			.saturating_add(RocksDbWeight::get().writes((4_u64).saturating_mul(b.into())))
+			.saturating_add(RocksDbWeight::get().writes((1_u64).saturating_mul(a.into())))
+	}
+	/// Storage: Escrows (r:1 w:1)
+	/// Storage: EscrowReleaseQueue (r:0 w:1)
+	/// Storage: System Account (r:1 w:1)
+	fn release_escrow() -> Weight {
+		Weight::from_parts(15_000_000, 3_593)
+			.saturating_add(RocksDbWeight::get().reads(2_u64))
+			.saturating_add(RocksDbWeight::get().writes(3_u64))
+	}
+	/// Storage: Settlements (r:0 w:1)
+	/// Storage: SettlementPruningQueue (r:0 w:1)
+	fn prune_settlement() -> Weight {
+		Weight::from_parts(5_000_000, 0)
+			.saturating_add(RocksDbWeight::get().writes(2_u64))
+	}
+	/// Storage: Schedules (r:1 w:1)
+	/// Storage: ScheduleQueue (r:0 w:2)
+	/// Storage: MarketState (r:1 w:0)
+	/// Storage: AuctionIndex (r:1 w:1)
+	/// Storage: Auctions (r:0 w:1)
+	/// Storage: AuctionsExecutionQueue (r:0 w:1)
+	/// Storage: SellerAuctions (r:0 w:1)
+	/// Storage: ListingDeposits (r:0 w:1)
+	/// Storage: AuctionHistoryCount (r:1 w:1)
+	/// Storage: AuctionHistory (r:0 w:1)
+	/// Storage: Reputations (r:1 w:0)
+	/// Storage: TierPriceLimits (r:1 w:0)
+	/// Storage: PeriodPriceLimits (r:1 w:0)
+	/// Storage: System Account (r:2 w:2)
+	fn run_schedule() -> Weight {
+		Weight::from_parts(95_000_000, 6_196)
+			.saturating_add(RocksDbWeight::get().reads(10_u64))
+			.saturating_add(RocksDbWeight::get().writes(13_u64))
+	}
+	/// Storage: MarketState (r:1 w:0)
+	/// Storage: AuctionIndex (r:n w:n)
+	/// Storage: Auctions (r:0 w:n)
+	/// Storage: AuctionsExecutionQueue (r:0 w:n)
+	/// Storage: SellerAuctions (r:0 w:n)
+	/// Storage: ListingDeposits (r:0 w:n)
+	/// Storage: AuctionHistoryCount (r:n w:n)
+	/// Storage: AuctionHistory (r:0 w:n)
+	/// Storage: Reputations (r:n w:0)
+	/// Storage: TierPriceLimits (r:n w:0)
+	/// Storage: PeriodPriceLimits (r:n w:0)
+	/// Storage: System Account (r:2 w:2)
+	/// The range of component `n` is `[1, MaxBatchSize]`.
+	fn new_batch(n: u32) -> Weight {
+		Weight::from_parts(12_000_000, 6_196)
+			.saturating_add(Weight::from_parts(85_000_000, 3_513).saturating_mul(n.into()))
+			.saturating_add(RocksDbWeight::get().reads(3_u64))
+			.saturating_add(RocksDbWeight::get().reads((5_u64).saturating_mul(n.into())))
+			.saturating_add(RocksDbWeight::get().writes(2_u64))
+			.saturating_add(RocksDbWeight::get().writes((8_u64).saturating_mul(n.into())))
+	}
+	/// Storage: MarketState (r:n w:0)
+	/// Storage: Auctions (r:n w:n)
+	/// Storage: MinSellerReputation (r:1 w:0)
+	/// Storage: Reputations (r:n w:0)
+	/// Storage: TierPriceLimits (r:n w:0)
+	/// Storage: PeriodPriceLimits (r:n w:0)
+	/// Storage: LiveBids (r:1 w:n)
+	/// Storage: BidDeposits (r:n w:n)
+	/// Storage: BuyerAuctions (r:n w:n)
+	/// Storage: AuctionHistoryCount (r:n w:n)
+	/// Storage: AuctionHistory (r:0 w:n)
+	/// Storage: AutoBids (r:n w:0)
+	/// Storage: System Account (r:2n w:2n)
+	/// The range of component `n` is `[1, MaxBatchSize]`.
+	fn bid_batch(n: u32) -> Weight {
+		Weight::from_parts(10_000_000, 3_593)
+			.saturating_add(Weight::from_parts(90_000_000, 7_186).saturating_mul(n.into()))
+			.saturating_add(RocksDbWeight::get().reads(2_u64))
+			.saturating_add(RocksDbWeight::get().reads((11_u64).saturating_mul(n.into())))
+			.saturating_add(RocksDbWeight::get().writes((9_u64).saturating_mul(n.into())))
+	}
+	/// Storage: MarketState (r:1 w:1)
+	/// Storage: HaltedSince (r:1 w:1)
+	/// Storage: Auctions (r:n w:n)
+	/// Storage: AuctionsExecutionQueue (r:0 w:2n)
+	/// The range of component `n` is `[0, listed auctions]`.
+	fn set_market_status(n: u32) -> Weight {
+		Weight::from_parts(8_000_000, 1_489)
+			.saturating_add(Weight::from_parts(7_000_000, 3_593).saturating_mul(n.into()))
+			.saturating_add(RocksDbWeight::get().reads(2_u64))
+			.saturating_add(RocksDbWeight::get().reads((1_u64).saturating_mul(n.into())))
+			.saturating_add(RocksDbWeight::get().writes(2_u64))
+			.saturating_add(RocksDbWeight::get().writes((3_u64).saturating_mul(n.into())))
+	}
+	/// Storage: Auctions (r:n w:n)
+	/// Storage: AutoBids (r:n^2 w:n)
+	/// Storage: MarketState (r:n w:0)
+	/// Storage: Reputations (r:n w:0)
+	/// Storage: TierPriceLimits (r:n w:0)
+	/// Storage: PeriodPriceLimits (r:n w:0)
+	/// Storage: LiveBids (r:n w:n)
+	/// Storage: BidDeposits (r:n w:n)
+	/// Storage: BuyerAuctions (r:n w:n)
+	/// Storage: AuctionHistoryCount (r:n w:n)
+	/// Storage: AuctionHistory (r:0 w:n)
+	/// Storage: System Account (r:2n w:2n)
+	/// The range of component `n` is `[0, MaxAutoBids]`. Every auto bid may be outbid or fail
+	/// once, each time reading all of them again.
+	fn resolve_auto_bids(n: u32) -> Weight {
+		Weight::from_parts(2_000_000, 1_489)
+			.saturating_add(Weight::from_parts(90_000_000, 7_186).saturating_mul(n.into()))
+			.saturating_add(RocksDbWeight::get().reads(1_u64))
+			.saturating_add(RocksDbWeight::get().reads((u64::from(n) + 11).saturating_mul(n.into())))
+			.saturating_add(RocksDbWeight::get().writes((10_u64).saturating_mul(n.into())))
 	}
 }
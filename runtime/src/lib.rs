@@ -27,8 +27,8 @@ use sp_version::RuntimeVersion;
 pub use frame_support::{
 	construct_runtime, parameter_types,
 	traits::{
-		ConstBool, ConstU128, ConstU32, ConstU64, ConstU8, KeyOwnerProofSystem, Randomness,
-		StorageInfo,
+		ConstBool, ConstU128, ConstU16, ConstU32, ConstU64, ConstU8, KeyOwnerProofSystem,
+		Randomness, StorageInfo,
 	},
 	weights::{
 		constants::{
@@ -36,9 +36,10 @@ pub use frame_support::{
 		},
 		IdentityFee, Weight,
 	},
-	StorageValue,
+	PalletId, StorageValue,
 };
 pub use frame_system::Call as SystemCall;
+use frame_system::EnsureRoot;
 pub use pallet_balances::Call as BalancesCall;
 pub use pallet_timestamp::Call as TimestampCall;
 use pallet_transaction_payment::{ConstFeeMultiplier, CurrencyAdapter, Multiplier};
@@ -48,6 +49,7 @@ pub use sp_runtime::{Perbill, Permill};
 
 /// Import the template pallet.
 pub use energy_bidding;
+use energy_bidding_runtime_api::Market;
 
 /// An index to a block.
 pub type BlockNumber = u32;
@@ -104,10 +106,10 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
 	//   `spec_version`, and `authoring_version` are the same between Wasm and native.
 	// This value is set to 100 to notify Polkadot-JS App (https://polkadot.js.org/apps) to use
 	//   the compatible custom types.
-	spec_version: 100,
+	spec_version: 106,
 	impl_version: 1,
 	apis: RUNTIME_API_VERSIONS,
-	transaction_version: 1,
+	transaction_version: 5,
 	state_version: 1,
 };
 
@@ -268,13 +270,121 @@ impl pallet_sudo::Config for Runtime {
 	type WeightInfo = pallet_sudo::weights::SubstrateWeight<Runtime>;
 }
 
-/// Configure the pallet-template in pallets/template.
-impl energy_bidding::Config for Runtime {
+parameter_types! {
+	pub const DayAheadPalletId: PalletId = PalletId(*b"py/enbid");
+	pub const DayAheadTradeFee: Perbill = Perbill::from_percent(1);
+	pub const DayAheadListingFee: Balance = 10 * EXISTENTIAL_DEPOSIT;
+	pub const IntradayPalletId: PalletId = PalletId(*b"py/intra");
+	pub const IntradayTradeFee: Perbill = Perbill::from_percent(2);
+	pub const IntradayListingFee: Balance = 5 * EXISTENTIAL_DEPOSIT;
+	pub const DayAheadCircuitBreaker: Perbill = Perbill::from_percent(50);
+	pub const IntradayCircuitBreaker: Perbill = Perbill::from_percent(30);
+	pub const DisputeWindow: BlockNumber = DAYS;
+	pub const ReputationRetention: Perbill = Perbill::from_percent(90);
+	pub const ReputationDecayPeriod: BlockNumber = 7 * DAYS;
+	pub const RetractionPenalty: Perbill = Perbill::from_percent(5);
+	pub const BidDeposit: Balance = EXISTENTIAL_DEPOSIT;
+	pub const SettlementRetention: BlockNumber = 90 * DAYS;
+	pub const PriceIndexWindow: BlockNumber = DAYS;
+	pub const SponsorshipPeriod: BlockNumber = DAYS;
+	pub const ListingDeposit: Balance = 100 * EXISTENTIAL_DEPOSIT;
+	pub const MaxMarketHookWeight: Weight = Weight::zero();
+	pub const RelistDiscount: Perbill = Perbill::from_percent(5);
+}
+
+/// The market for energy delivered on the next day, auctioned up to a day ahead.
+pub type DayAheadInstance = energy_bidding::Instance1;
+/// The market for energy delivered within the hour.
+pub type IntradayInstance = energy_bidding::Instance2;
+
+impl energy_bidding::Config<DayAheadInstance> for Runtime {
+	type RuntimeEvent = RuntimeEvent;
+	type AuctionId = u64;
+	type Quantity = u128;
+	type Price = u128;
+	type Currency = Balances;
+	type PalletId = DayAheadPalletId;
+	type TradeFee = DayAheadTradeFee;
+	type ListingFee = DayAheadListingFee;
+	type TreasuryOrigin = EnsureRoot<AccountId>;
+	type DisputeWindow = DisputeWindow;
+	type ArbitratorOrigin = EnsureRoot<AccountId>;
+	type ReputationRetention = ReputationRetention;
+	type ReputationDecayPeriod = ReputationDecayPeriod;
+	type MaxBidsPerAuction = ConstU32<10>;
+	type MaxLiveBids = ConstU32<200>;
+	type MaxAutoBids = ConstU32<20>;
+	type MaxGroupMembers = ConstU32<50>;
+	type BidDeposit = BidDeposit;
+	type RetractionPenalty = RetractionPenalty;
+	type SettlementRetention = SettlementRetention;
+	type ListingDeposit = ListingDeposit;
+	type RelistDiscount = RelistDiscount;
+	type MaxRelistRounds = ConstU32<3>;
+	type MaxProcessedPerBlock = ConstU32<50>;
+	type MaxBlocksScannedPerBlock = ConstU32<100>;
+	type DeliveryPeriodLength = ConstU32<HOURS>;
+	type MaxBatchSize = ConstU32<32>;
+	type MaxPriceObservations = ConstU32<256>;
+	type PriceIndexWindow = PriceIndexWindow;
+	type MaxAuctionPeriod = ConstU16<{ 24 * 60 }>;
+	type LargeTierThreshold = ConstU128<100>;
+	type RegulatorOrigin = EnsureRoot<AccountId>;
+	type PauseOrigin = EnsureRoot<AccountId>;
+	type CircuitBreakerThreshold = DayAheadCircuitBreaker;
+	type SponsorshipPeriod = SponsorshipPeriod;
+	type SponsoredCallsPerPeriod = ConstU32<10>;
+	type MaxSponsoredFailures = ConstU32<3>;
+	type OnAuctionCreated = ();
+	type OnAuctionCanceled = ();
+	type MaxMarketHookWeight = MaxMarketHookWeight;
+	type OnAuctionExecuted = ();
+	type WeightInfo = energy_bidding::weights::SubstrateWeight<Runtime>;
+}
+
+impl energy_bidding::Config<IntradayInstance> for Runtime {
 	type RuntimeEvent = RuntimeEvent;
-	// type WeightInfo = energy_bidding::weights::SubstrateWeight<Runtime>;
 	type AuctionId = u64;
 	type Quantity = u128;
 	type Price = u128;
+	type Currency = Balances;
+	type PalletId = IntradayPalletId;
+	type TradeFee = IntradayTradeFee;
+	type ListingFee = IntradayListingFee;
+	type TreasuryOrigin = EnsureRoot<AccountId>;
+	type DisputeWindow = DisputeWindow;
+	type ArbitratorOrigin = EnsureRoot<AccountId>;
+	type ReputationRetention = ReputationRetention;
+	type ReputationDecayPeriod = ReputationDecayPeriod;
+	type MaxBidsPerAuction = ConstU32<5>;
+	type MaxLiveBids = ConstU32<50>;
+	type MaxAutoBids = ConstU32<10>;
+	type MaxGroupMembers = ConstU32<50>;
+	type BidDeposit = BidDeposit;
+	type RetractionPenalty = RetractionPenalty;
+	type SettlementRetention = SettlementRetention;
+	type ListingDeposit = ListingDeposit;
+	type RelistDiscount = RelistDiscount;
+	type MaxRelistRounds = ConstU32<1>;
+	type MaxProcessedPerBlock = ConstU32<50>;
+	type MaxBlocksScannedPerBlock = ConstU32<100>;
+	type DeliveryPeriodLength = ConstU32<HOURS>;
+	type MaxBatchSize = ConstU32<32>;
+	type MaxPriceObservations = ConstU32<256>;
+	type PriceIndexWindow = PriceIndexWindow;
+	type MaxAuctionPeriod = ConstU16<60>;
+	type LargeTierThreshold = ConstU128<5>;
+	type RegulatorOrigin = EnsureRoot<AccountId>;
+	type PauseOrigin = EnsureRoot<AccountId>;
+	type CircuitBreakerThreshold = IntradayCircuitBreaker;
+	type SponsorshipPeriod = SponsorshipPeriod;
+	type SponsoredCallsPerPeriod = ConstU32<10>;
+	type MaxSponsoredFailures = ConstU32<3>;
+	type OnAuctionCreated = ();
+	type OnAuctionCanceled = ();
+	type MaxMarketHookWeight = MaxMarketHookWeight;
+	type OnAuctionExecuted = ();
+	type WeightInfo = energy_bidding::weights::SubstrateWeight<Runtime>;
 }
 
 // Create the runtime by composing the FRAME pallets that were previously configured.
@@ -288,7 +398,8 @@ construct_runtime!(
 		TransactionPayment: pallet_transaction_payment,
 		Sudo: pallet_sudo,
 		// Include the custom logic from the pallet-template in the runtime.
-		EnergyBiddingModule: energy_bidding,
+		DayAheadMarket: energy_bidding::<Instance1>,
+		IntradayMarket: energy_bidding::<Instance2>,
 	}
 );
 
@@ -307,14 +418,67 @@ pub type SignedExtra = (
 	frame_system::CheckEra<Runtime>,
 	frame_system::CheckNonce<Runtime>,
 	frame_system::CheckWeight<Runtime>,
-	pallet_transaction_payment::ChargeTransactionPayment<Runtime>,
+	ChargeMarketFees,
 );
+/// Charges transaction fees, except for the market calls of registered small prosumers.
+pub type ChargeMarketFees = energy_bidding::SponsorMarketFees<
+	Runtime,
+	DayAheadInstance,
+	energy_bidding::SponsorMarketFees<
+		Runtime,
+		IntradayInstance,
+		pallet_transaction_payment::ChargeTransactionPayment<Runtime>,
+	>,
+>;
 
 /// Unchecked extrinsic type as expected by this runtime.
 pub type UncheckedExtrinsic =
 	generic::UncheckedExtrinsic<Address, RuntimeCall, Signature, SignedExtra>;
 /// The payload being signed in transactions.
 pub type SignedPayload = generic::SignedPayload<RuntimeCall, SignedExtra>;
+/// Moves the storage of the former single `EnergyBiddingModule` under `DayAheadMarket`, marks
+/// the storage of the newly added `IntradayMarket` as up to date and creates both treasuries.
+pub struct SplitEnergyMarkets;
+
+impl frame_support::traits::OnRuntimeUpgrade for SplitEnergyMarkets {
+	fn on_runtime_upgrade() -> Weight {
+		use frame_support::{
+			storage::{unhashed, PrefixIterator},
+			traits::{GetStorageVersion, PalletInfoAccess},
+		};
+		type DbWeight = <Runtime as frame_system::Config>::DbWeight;
+
+		if IntradayMarket::on_chain_storage_version() != 0 {
+			return DbWeight::get().reads(1)
+		}
+
+		// Same as `move_pallet`, but counting the keys moved.
+		let from = sp_core::hashing::twox_128(b"EnergyBiddingModule");
+		let to = sp_core::hashing::twox_128(DayAheadMarket::name().as_bytes());
+		let moved =
+			PrefixIterator::<(Vec<u8>, Vec<u8>)>::new(from.to_vec(), from.to_vec(), |key, value| {
+				Ok((key.to_vec(), value.to_vec()))
+			})
+			.drain()
+			.map(|(key, value)| unhashed::put_raw(&[&to[..], &key].concat(), &value))
+			.count() as u64;
+
+		IntradayMarket::current_storage_version().put::<IntradayMarket>();
+
+		// Same as the genesis build: the treasuries must exist so small fees are not lost as
+		// dust. The single market's treasury predates trade fees and may not exist either.
+		let funded =
+			DayAheadMarket::ensure_treasury() as u64 + IntradayMarket::ensure_treasury() as u64;
+
+		// Funding a treasury writes its account and the total issuance.
+		DbWeight::get().reads_writes(moved + 3 + funded, 2 * moved + 1 + 2 * funded)
+	}
+}
+
+/// Storage migrations applied on runtime upgrade.
+pub type Migrations =
+	(SplitEnergyMarkets, energy_bidding::migrations::v2::MigrateToV2<Runtime, DayAheadInstance>);
+
 /// Executive: handles dispatch to the various modules.
 pub type Executive = frame_executive::Executive<
 	Runtime,
@@ -322,6 +486,7 @@ pub type Executive = frame_executive::Executive<
 	frame_system::ChainContext<Runtime>,
 	Runtime,
 	AllPalletsWithSystem,
+	Migrations,
 >;
 
 #[cfg(feature = "runtime-benchmarks")]
@@ -336,7 +501,7 @@ mod benches {
 		[pallet_balances, Balances]
 		[pallet_timestamp, Timestamp]
 		[pallet_sudo, Sudo]
-		[energy_bidding, EnergyBiddingModule]
+		[energy_bidding, DayAheadMarket]
 	);
 }
 
@@ -485,6 +650,88 @@ impl_runtime_apis! {
 		}
 	}
 
+	impl energy_bidding_runtime_api::EnergyBiddingApi<
+		Block,
+		AccountId,
+		u64,
+		u128,
+		u128,
+		BlockNumber,
+		Balance,
+	> for Runtime {
+		fn open_auctions(
+			market: Market,
+			tier: Option<u32>,
+			page: u32,
+			page_size: u32,
+		) -> Vec<energy_bidding::AuctionDataOf<Runtime, DayAheadInstance>> {
+			match market {
+				Market::DayAhead => DayAheadMarket::open_auctions(tier, page, page_size),
+				Market::Intraday => IntradayMarket::open_auctions(tier, page, page_size),
+			}
+		}
+
+		fn auction(
+			market: Market,
+			auction_id: u64,
+		) -> Option<energy_bidding::AuctionDataOf<Runtime, DayAheadInstance>> {
+			match market {
+				Market::DayAhead => DayAheadMarket::auctions(auction_id),
+				Market::Intraday => IntradayMarket::auctions(auction_id),
+			}
+		}
+
+		fn bids_of(
+			market: Market,
+			account: AccountId,
+		) -> Vec<(u64, energy_bidding::Bid<AccountId, u128>)> {
+			match market {
+				Market::DayAhead => DayAheadMarket::bids_of(&account),
+				Market::Intraday => IntradayMarket::bids_of(&account),
+			}
+		}
+
+		fn highest_bid(
+			market: Market,
+			auction_id: u64,
+		) -> Option<energy_bidding::Bid<AccountId, u128>> {
+			match market {
+				Market::DayAhead => DayAheadMarket::highest_bid(auction_id),
+				Market::Intraday => IntradayMarket::highest_bid(auction_id),
+			}
+		}
+
+		fn time_remaining(market: Market, auction_id: u64) -> Option<BlockNumber> {
+			match market {
+				Market::DayAhead => DayAheadMarket::time_remaining(auction_id),
+				Market::Intraday => IntradayMarket::time_remaining(auction_id),
+			}
+		}
+
+		fn account_history(
+			market: Market,
+			account: AccountId,
+			page: u32,
+			page_size: u32,
+		) -> Vec<(u64, energy_bidding::PartyType)> {
+			match market {
+				Market::DayAhead => DayAheadMarket::account_history(&account, page, page_size),
+				Market::Intraday => IntradayMarket::account_history(&account, page, page_size),
+			}
+		}
+
+		fn quote_bid(
+			market: Market,
+			auction_id: u64,
+			bid: u128,
+		) -> Option<energy_bidding::BidQuote<u128, Balance>> {
+			match market {
+				Market::DayAhead => DayAheadMarket::quote_bid(auction_id, bid),
+				Market::Intraday => IntradayMarket::quote_bid(auction_id, bid),
+			}
+		}
+	}
+
 	impl pallet_transaction_payment_rpc_runtime_api::TransactionPaymentCallApi<Block, Balance, RuntimeCall>
 		for Runtime
 	{